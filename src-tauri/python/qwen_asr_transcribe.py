@@ -1,7 +1,11 @@
 #!/usr/bin/env python3
 import argparse
+import contextlib
 import importlib
+import json
+import re
 import sys
+import time
 
 
 def parse_args() -> argparse.Namespace:
@@ -9,21 +13,221 @@ def parse_args() -> argparse.Namespace:
         description="Transcribe an audio file using Qwen ASR"
     )
     parser.add_argument("--audio", help="Path to WAV/PCM input audio")
-    parser.add_argument("--model", required=True, help="Hugging Face model id")
+    parser.add_argument("--model", help="Hugging Face model id")
     parser.add_argument("--language", default="auto", help="Language name or auto")
     parser.add_argument(
         "--warmup",
         action="store_true",
         help="Only load model and exit to pre-download/check runtime",
     )
+    parser.add_argument(
+        "--device",
+        default="auto",
+        choices=["auto", "cpu", "cuda", "mps"],
+        help="Compute device to run the model on",
+    )
+    parser.add_argument(
+        "--max-new-tokens",
+        type=int,
+        default=768,
+        help="Upper bound on generated tokens per transcription",
+    )
+    parser.add_argument(
+        "--beam-size",
+        type=int,
+        default=None,
+        help="Beam search width; omitted uses the model's default",
+    )
+    parser.add_argument(
+        "--temperature",
+        type=float,
+        default=None,
+        help="Sampling temperature; omitted or 0 decodes greedily",
+    )
+    parser.add_argument(
+        "--timestamps",
+        action="store_true",
+        help="Also report segment start/end times (downloads a forced aligner once)",
+    )
+    parser.add_argument(
+        "--wake-word",
+        default=None,
+        help="Stay loaded and report whether each WAV path read from stdin contains this phrase",
+    )
+    parser.add_argument(
+        "--probe",
+        action="store_true",
+        help="Report available compute devices as JSON and exit",
+    )
+    parser.add_argument(
+        "--info",
+        action="store_true",
+        help="Report torch, device and cached model details as JSON and exit",
+    )
+    parser.add_argument(
+        "--candidates",
+        nargs="*",
+        default=[],
+        help="Model ids to check in the local cache when using --info",
+    )
     args = parser.parse_args()
 
-    if not args.warmup and not args.audio:
-        parser.error("--audio is required unless --warmup is used")
+    if args.probe or args.info:
+        return args
+
+    if not args.model:
+        parser.error("--model is required unless --probe is used")
+
+    if not args.warmup and not args.wake_word and not args.audio:
+        parser.error("--audio is required unless --warmup or --wake-word is used")
 
     return args
 
 
+def emit_result(payload: dict) -> None:
+    """Print the single JSON object the Rust side parses from stdout."""
+    sys.stdout.write(json.dumps(payload) + "\n")
+    sys.stdout.flush()
+
+
+def emit_stage(stage: str) -> None:
+    """Report progress to the Rust side. Writes to the real stdout, since
+    library output is redirected to stderr while the model runs."""
+    sys.__stdout__.write(json.dumps({"stage": stage}) + "\n")
+    sys.__stdout__.flush()
+
+
+def mps_available(torch) -> bool:
+    mps_backend = getattr(torch.backends, "mps", None)
+    return bool(mps_backend is not None and mps_backend.is_available())
+
+
+def probe_compute(torch) -> dict:
+    cuda = torch.cuda.is_available()
+    mps = mps_available(torch)
+    return {
+        "cuda": cuda,
+        "cuda_device": torch.cuda.get_device_name(0) if cuda else None,
+        "mps": mps,
+    }
+
+
+def resolve_device(torch, requested: str):
+    """Map the --device choice to a (device_map, dtype) pair."""
+    cuda = torch.cuda.is_available()
+    mps = mps_available(torch)
+
+    if requested == "auto":
+        requested = "cuda" if cuda else "cpu"
+
+    if requested == "cuda":
+        if not cuda:
+            raise RuntimeError("CUDA was requested but is not available")
+        return "cuda:0", torch.float16
+
+    if requested == "mps":
+        if not mps:
+            raise RuntimeError("MPS was requested but is not available")
+        return "mps", torch.float16
+
+    return "cpu", torch.float32
+
+
+def is_model_cached(model_id: str) -> bool:
+    try:
+        hub = importlib.import_module("huggingface_hub")
+        cached = hub.try_to_load_from_cache(model_id, "config.json")
+    except Exception:
+        return False
+    return isinstance(cached, str)
+
+
+def environment_info(torch, requested_device: str, candidates) -> dict:
+    probe = probe_compute(torch)
+    try:
+        device, _ = resolve_device(torch, requested_device)
+    except RuntimeError:
+        device = "unavailable"
+
+    return {
+        "torch_version": torch.__version__,
+        "cuda_version": torch.version.cuda,
+        "cuda_device": probe["cuda_device"],
+        "device": device,
+        "cached_models": {model_id: is_model_cached(model_id) for model_id in candidates},
+    }
+
+
+FORCED_ALIGNER_MODEL = "Qwen/Qwen3-ForcedAligner-0.6B"
+
+
+def timestamp_segments(result) -> list:
+    """Segments with millisecond offsets from a result transcribed with
+    time stamps; empty when the model returned none."""
+    segments = []
+    for stamp in getattr(result, "time_stamps", None) or []:
+        text = str(getattr(stamp, "text", "")).strip()
+        if not text:
+            continue
+        segments.append(
+            {
+                "text": text,
+                "start_ms": int(float(getattr(stamp, "start_time", 0.0)) * 1000),
+                "end_ms": int(float(getattr(stamp, "end_time", 0.0)) * 1000),
+            }
+        )
+    return segments
+
+
+def generation_options(args: argparse.Namespace) -> dict:
+    """Decoding options forwarded to the model; unset ones keep its defaults."""
+    options = {"max_new_tokens": args.max_new_tokens}
+    if args.beam_size is not None:
+        options["num_beams"] = args.beam_size
+    if args.temperature:
+        options["do_sample"] = True
+        options["temperature"] = args.temperature
+    return options
+
+
+def phrase_words(text: str) -> list:
+    return re.findall(r"[\w']+", text.lower())
+
+
+def contains_phrase(text: str, phrase: str) -> bool:
+    """Whole-word, case- and punctuation-insensitive phrase match."""
+    words = phrase_words(text)
+    target = phrase_words(phrase)
+    if not target:
+        return False
+    return any(
+        words[index : index + len(target)] == target
+        for index in range(len(words) - len(target) + 1)
+    )
+
+
+def listen_for_wake_word(model, args: argparse.Namespace) -> int:
+    """Answer each audio path on stdin with one {"wake": bool} line until
+    stdin closes, keeping the model loaded between windows."""
+    language = None if args.language.lower() == "auto" else args.language
+    emit_result({"ready": True})
+
+    for line in sys.stdin:
+        path = line.strip()
+        if not path:
+            continue
+        try:
+            with contextlib.redirect_stdout(sys.stderr):
+                results = model.transcribe(audio=path, language=language)
+            text = results[0].text.strip() if results else ""
+        except Exception as exc:
+            print(f"Wake word check failed: {exc}", file=sys.stderr)
+            text = ""
+        emit_result({"wake": contains_phrase(text, args.wake_word)})
+
+    return 0
+
+
 def main() -> int:
     args = parse_args()
 
@@ -39,29 +243,77 @@ def main() -> int:
         print(str(exc), file=sys.stderr)
         return 2
 
-    use_cuda = torch.cuda.is_available()
-    dtype = torch.float16 if use_cuda else torch.float32
-    device_map = "cuda:0" if use_cuda else "cpu"
+    if args.probe:
+        emit_result(probe_compute(torch))
+        return 0
+
+    if args.info:
+        emit_result(environment_info(torch, args.device, args.candidates))
+        return 0
+
+    try:
+        device_map, dtype = resolve_device(torch, args.device)
+    except RuntimeError as exc:
+        print(str(exc), file=sys.stderr)
+        return 4
 
     try:
         if qwen_model is None:
             raise RuntimeError("qwen_asr.Qwen3ASRModel is unavailable")
 
-        model = qwen_model.from_pretrained(
-            args.model,
-            dtype=dtype,
-            device_map=device_map,
-            max_new_tokens=768,
-        )
+        # Library chatter must never reach stdout, which is reserved for the
+        # JSON result.
+        transcribing = not args.warmup and not args.wake_word
+        if transcribing:
+            emit_stage("loading")
+
+        with contextlib.redirect_stdout(sys.stderr):
+            aligner_options = {}
+            if args.timestamps:
+                aligner_options = {
+                    "forced_aligner": FORCED_ALIGNER_MODEL,
+                    "forced_aligner_kwargs": {"dtype": dtype, "device_map": device_map},
+                }
+            model = qwen_model.from_pretrained(
+                args.model,
+                dtype=dtype,
+                device_map=device_map,
+                **generation_options(args),
+                **aligner_options,
+            )
+
+            if transcribing:
+                language = None if args.language.lower() == "auto" else args.language
+                emit_stage("decoding")
+                started = time.perf_counter()
+                results = model.transcribe(
+                    audio=args.audio,
+                    language=language,
+                    return_time_stamps=args.timestamps,
+                )
+                duration_ms = int((time.perf_counter() - started) * 1000)
 
         if args.warmup:
-            print("READY")
+            emit_result({"ready": True})
             return 0
 
-        language = None if args.language.lower() == "auto" else args.language
-        results = model.transcribe(audio=args.audio, language=language)
-        text = results[0].text.strip() if results else ""
-        print(text)
+        if args.wake_word:
+            return listen_for_wake_word(model, args)
+
+        result = results[0] if results else None
+        text = result.text.strip() if result is not None else ""
+        detected = getattr(result, "language", None) or language
+        emit_result(
+            {
+                "text": text,
+                "language": detected,
+                "duration_ms": duration_ms,
+                # A result with no words means silence; no result at all
+                # means the model failed.
+                "no_speech": result is not None and not text,
+                "segments": timestamp_segments(result) if args.timestamps else [],
+            }
+        )
         return 0
     except Exception as exc:
         print(f"Transcription failed: {exc}", file=sys.stderr)
@@ -1,13 +1,16 @@
 use std::{
+    collections::{HashMap, VecDeque},
     fs,
+    io::{BufRead, BufReader, Read, Write},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
-    process::Command,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
     sync::{
-        mpsc::{self, Receiver, Sender},
-        Arc, Mutex,
+        mpsc::{self, Receiver, Sender, SyncSender},
+        Arc, Mutex, OnceLock,
     },
     thread,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(windows)]
@@ -16,27 +19,138 @@ use std::os::windows::process::CommandExt;
 use arboard::Clipboard;
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
-    SampleFormat, Stream, StreamConfig,
+    FromSample, Sample, SampleFormat, SampleRate, SizedSample, Stream, StreamConfig, StreamError,
+    SupportedStreamConfig,
 };
 use enigo::{
     Direction::{Click, Press, Release},
     Enigo, Key, Keyboard, Settings,
 };
-use hound::{SampleFormat as WavSampleFormat, WavSpec, WavWriter};
+use hound::{SampleFormat as WavSampleFormat, WavReader, WavSpec, WavWriter};
 use serde::{Deserialize, Serialize};
 use tauri::{
-    menu::{Menu, MenuItem},
+    include_image,
+    menu::{CheckMenuItem, Menu, MenuItem},
     tray::TrayIconBuilder,
-    AppHandle, Emitter, Manager, PhysicalPosition, Position, State, WebviewUrl,
-    WebviewWindowBuilder, WindowEvent,
+    AppHandle, Emitter, Manager, Monitor, PhysicalPosition, Position, State, Theme, WebviewUrl,
+    WebviewWindow, WebviewWindowBuilder, WindowEvent, Wry,
 };
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_global_shortcut::{
+    Error as ShortcutError, GlobalShortcutExt, Shortcut, ShortcutState,
+};
+use tauri_plugin_opener::OpenerExt;
 
 const SETTINGS_FILE: &str = "settings.json";
+const HISTORY_FILE: &str = "history.jsonl";
+const DEFAULT_HISTORY_LIMIT: usize = 200;
+/// Past this size the history log drops its oldest entries down to half.
+const MAX_HISTORY_BYTES: u64 = 1024 * 1024;
+const SETTINGS_VERSION: u32 = 1;
+const TRAY_ID: &str = "dictation-tray";
 const DICTATION_EVENT: &str = "dictation-state";
 const TRANSCRIPT_EVENT: &str = "dictation-transcript";
+const DETECTED_LANGUAGE_EVENT: &str = "dictation-language";
+const COMPLETE_EVENT: &str = "dictation-complete";
+const INSTALL_EVENT: &str = "dependency-install";
+const INPUT_DEVICE_EVENT: &str = "dictation-input-device";
+const PENDING_EVENT: &str = "dictation-pending";
+const PROGRESS_EVENT: &str = "transcription-progress";
+const BLOCKED_EVENT: &str = "dictation-blocked";
+const THEME_EVENT: &str = "theme-changed";
+const DEVICES_EVENT: &str = "input-devices-changed";
+const INJECTION_EVENT: &str = "injection-result";
+const LANGUAGE_SUGGESTION_EVENT: &str = "language-pin-suggested";
 const OVERLAY_LABEL: &str = "overlay";
+const REVIEW_LABEL: &str = "review";
+const HISTORY_LABEL: &str = "history";
 const DEFAULT_INPUT_DEVICE: &str = "default";
+const DEFAULT_OUTPUT_DEVICE: &str = "default";
+const RECORDING_PREFIX: &str = "dictation-";
+const RECORDING_EXTENSION: &str = "wav";
+const PREFERRED_SAMPLE_RATE: u32 = 16_000;
+const CAPTURE_SAMPLE_RATE_RANGE: std::ops::RangeInclusive<u32> = 8_000..=192_000;
+const STALE_RECORDING_AGE: Duration = Duration::from_secs(60 * 60);
+const FALLBACK_RECORDINGS_DIR: &str = "delulu-talks-recordings";
+const KEPT_RECORDINGS_DIR: &str = "recordings";
+const FALLBACK_APP_DIR: &str = ".delulu";
+/// Resource subdirectory holding an embeddable Python, when a build ships one.
+const BUNDLED_PYTHON_DIR: &str = "python-runtime";
+const TRANSCRIPTION_CANCELLED: &str = "Transcription cancelled";
+const BOOTSTRAP_SUPERSEDED: &str = "Bootstrap superseded by a newer one";
+const EMPTY_TRANSCRIPT: &str = "ASR returned empty transcript";
+const NOTHING_HEARD: &str = "Nothing heard";
+const RUNTIME_NOT_READY: &str = "ASR setup still running. Please wait...";
+const TEMPLATE_PLACEHOLDER: &str = "{text}";
+const MESSAGE_PLACEHOLDER: &str = "{message}";
+const INPUT_AUTOMATION_UNAVAILABLE: &str = "Input automation init failed";
+/// Import name and pip package of each ASR dependency.
+const PYTHON_DEPENDENCIES: [(&str, &str); 3] = [
+    ("qwen_asr", "qwen-asr"),
+    ("torch", "torch"),
+    ("torchvision", "torchvision"),
+];
+const TRANSCRIBABLE_EXTENSIONS: [&str; 8] =
+    ["wav", "mp3", "flac", "ogg", "opus", "m4a", "aac", "webm"];
+const BLOCKED_LINGER: Duration = Duration::from_millis(1_500);
+const REVIEW_FOCUS_DELAY: Duration = Duration::from_millis(150);
+const REVIEW_FOCUS_TIMEOUT: Duration = Duration::from_millis(1_500);
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(25);
+const PRE_PASTE_DELAY: Duration = Duration::from_millis(40);
+const MAX_INJECTION_DELAY_MS: u64 = 2_000;
+const MAX_OVERLAY_HIDE_DELAY_MS: u64 = 10_000;
+const MAX_TYPE_CHUNK_DELAY_MS: u64 = 1_000;
+const MAX_MIC_TEST_SECS: u64 = 10;
+const MAX_RECORDER_RETRIES: u32 = 5;
+const RECORDER_RETRY_DELAY: Duration = Duration::from_millis(300);
+/// Shorter captures come from accidental taps and are dropped untranscribed.
+const MIN_RECORDING: Duration = Duration::from_millis(100);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+const MIN_PROGRESS_INTERVAL_MS: u64 = 100;
+const MAX_NEW_TOKENS_RANGE: std::ops::RangeInclusive<u32> = 16..=4_096;
+const BEAM_SIZE_RANGE: std::ops::RangeInclusive<u32> = 1..=10;
+const TEMPERATURE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+const MAX_PROGRESS_INTERVAL_MS: u64 = 10_000;
+const DEFAULT_CHUNK_LENGTH_SECS: u64 = 90;
+const DEFAULT_CHUNK_OVERLAP_SECS: u64 = 2;
+const CHUNK_LENGTH_RANGE: std::ops::RangeInclusive<u64> = 10..=600;
+/// Longest run of words compared when removing text repeated across a
+/// chunk overlap.
+const MAX_STITCH_OVERLAP_WORDS: usize = 30;
+/// How global-hotkey words its `AlreadyRegistered` and `FailedToRegister`
+/// errors; the shortcut plugin only passes their text on.
+const HOTKEY_TAKEN_ERRORS: [&str; 2] = ["HotKey already registered", "Unable to register hotkey"];
+const DEFAULT_SILENCE_THRESHOLD: f32 = 0.005;
+const DEFAULT_TRIM_THRESHOLD: f32 = 0.02;
+const DEFAULT_TRIM_MARGIN_MS: u64 = 200;
+const PERFORMANCE_HISTORY_LEN: usize = 50;
+const TEST_INJECTION_COUNTDOWN: Duration = Duration::from_secs(3);
+const POST_PROCESS_TIMEOUT: Duration = Duration::from_secs(15);
+const POST_PROCESS_POLL_INTERVAL: Duration = Duration::from_millis(25);
+const TEST_INJECTION_TEXT: &str =
+    "Delulu Talks test: héllo wörld, 你好, こんにちは, 안녕하세요 🎉🚀";
+const CUE_DURATION: Duration = Duration::from_millis(90);
+const CUE_FADE: Duration = Duration::from_millis(10);
+const CUE_VOLUME: f32 = 0.2;
+const ACTIVATION_DEBOUNCE: Duration = Duration::from_millis(150);
+const MODIFIER_POLL_INTERVAL: Duration = Duration::from_millis(15);
+const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(350);
+const RESUME_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const RESUME_GAP_THRESHOLD: Duration = Duration::from_secs(30);
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// The small model keeps always-on wake word checks cheap.
+const WAKE_MODEL: ModelOption = ModelOption::Qwen3Asr06b;
+const WAKE_WINDOW: Duration = Duration::from_secs(2);
+const WAKE_HOP: Duration = Duration::from_secs(1);
+/// Longest hop when detection runs slower than real time.
+const WAKE_MAX_HOP: Duration = Duration::from_secs(5);
+/// Capture buffers queued for the listener; more are dropped.
+const WAKE_QUEUE_CAPACITY: usize = 512;
+const WAKE_MIN_LEVEL: f32 = 0.01;
+const WAKE_END_SILENCE: Duration = Duration::from_millis(1500);
+const WAKE_NO_SPEECH_TIMEOUT: Duration = Duration::from_secs(5);
+const WAKE_START_GRACE: Duration = Duration::from_secs(1);
+const WAKE_READY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const WAKE_STALL_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -47,45 +161,445 @@ enum RecordingMode {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+enum ThemePreference {
+    System,
+    Light,
+    Dark,
+}
+
+/// Which monitor the overlay appears on when a dictation starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum OverlayMonitor {
+    /// The one showing the foreground window, where the text will land.
+    FocusedWindow,
+    Primary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum CaptureSource {
+    Microphone,
+    /// What the system is playing, for captioning meetings or videos.
+    SystemLoopback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum MouseButton {
+    Middle,
+    Back,
+    Forward,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum OutputMode {
+    InjectAtCursor,
+    ClipboardOnly,
+    Both,
+    /// Holds the transcript in the review window until the user confirms.
+    ConfirmBeforeInject,
+}
+
+impl OutputMode {
+    fn restores_clipboard(self) -> bool {
+        matches!(self, Self::InjectAtCursor | Self::ConfirmBeforeInject)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum InjectionMethod {
+    Paste,
+    Typing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppInjectionProfile {
+    app: String,
+    injection_method: InjectionMethod,
+    injection_delay_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VoiceCommand {
+    /// Spoken words, matched case-insensitively against whole words.
+    phrase: String,
+    /// Inserted in place of the phrase; takes the same escapes as
+    /// `append_suffix`.
+    output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShortcutProfile {
+    name: String,
+    shortcut: String,
+    recording_mode: RecordingMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RecordingFormat {
+    Pcm16,
+    Pcm24,
+    Float32,
+}
+
+impl RecordingFormat {
+    fn from_spec(spec: WavSpec) -> Self {
+        match (spec.sample_format, spec.bits_per_sample) {
+            (WavSampleFormat::Float, _) => Self::Float32,
+            (WavSampleFormat::Int, 24) => Self::Pcm24,
+            _ => Self::Pcm16,
+        }
+    }
+
+    fn wav_spec(self, channels: u16, sample_rate: u32) -> WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            Self::Pcm16 => (16, WavSampleFormat::Int),
+            Self::Pcm24 => (24, WavSampleFormat::Int),
+            Self::Float32 => (32, WavSampleFormat::Float),
+        };
+
+        WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ComputeDevice {
+    Auto,
+    Cpu,
+    Cuda,
+    Mps,
+}
+
+impl ComputeDevice {
+    fn as_arg(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Cpu => "cpu",
+            Self::Cuda => "cuda",
+            Self::Mps => "mps",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 enum ModelOption {
     Qwen3Asr17b,
     Qwen3Asr06b,
 }
 
 impl ModelOption {
+    const ALL: [Self; 2] = [Self::Qwen3Asr17b, Self::Qwen3Asr06b];
+
     fn as_hf_id(self) -> &'static str {
         match self {
             Self::Qwen3Asr17b => "Qwen/Qwen3-ASR-1.7B",
             Self::Qwen3Asr06b => "Qwen/Qwen3-ASR-0.6B",
         }
     }
+
+    /// Rough size of the first-run download, for setting expectations.
+    fn download_size(self) -> &'static str {
+        match self {
+            Self::Qwen3Asr17b => "~4.5 GB",
+            Self::Qwen3Asr06b => "~1.8 GB",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 struct AppSettings {
+    /// Schema version; files written before versioning deserialize as 0.
+    #[serde(default)]
+    version: u32,
     shortcut: String,
     recording_mode: RecordingMode,
+    /// Starts a dictation requested mid-transcription as soon as the
+    /// transcription finishes, instead of dropping the request.
+    queue_next_dictation: bool,
     model: ModelOption,
     language: String,
     python_command: String,
     input_device: String,
+    /// Tried in order when `input_device` is not connected.
+    fallback_devices: Vec<String>,
+    injection_delay_ms: u64,
+    output_mode: OutputMode,
+    injection_method: InjectionMethod,
+    /// Kill switch for simulated keystrokes. When off, transcripts still
+    /// reach the transcript event (and the clipboard in `Both` mode) but
+    /// nothing is pasted or typed.
+    auto_inject: bool,
+    app_profiles: Vec<AppInjectionProfile>,
+    silence_threshold: f32,
+    /// Ends a dictation that heard no words quietly on Idle instead of
+    /// reporting an error. Empty output from a failing model still errors.
+    quiet_empty_transcripts: bool,
+    compute_device: ComputeDevice,
+    audio_cues: bool,
+    /// Where audio cues play, independent of the capture device.
+    output_device: String,
+    trim_threshold: f32,
+    trim_margin_ms: u64,
+    /// Recordings longer than this are transcribed in overlapping windows
+    /// and stitched back together; 0 always sends the whole file.
+    chunk_length_secs: u64,
+    /// Audio shared by neighbouring windows so no word is cut in half.
+    chunk_overlap_secs: u64,
+    profiles: Vec<ShortcutProfile>,
+    active_profile: Option<usize>,
+    clipboard_fallback: bool,
+    recording_format: RecordingFormat,
+    /// Falls back to the clipboard when focus moved to another app while
+    /// dictating.
+    focus_guard: bool,
+    /// Falls back to the clipboard when nothing that takes text has focus,
+    /// e.g. after a click on the desktop.
+    unfocused_guard: bool,
+    /// Apps that never receive injected text, matched like `app_profiles`.
+    injection_denylist: Vec<String>,
+    /// Last language used with each model, restored when switching models.
+    model_languages: HashMap<ModelOption, String>,
+    /// With `language` on "auto", suggests pinning a language once it was
+    /// detected this many times in a row; 0 never suggests.
+    language_pin_after: u32,
+    /// Pins the language instead of only suggesting it.
+    auto_pin_language: bool,
+    /// Starts recording while the ASR runtime is still bootstrapping and
+    /// transcribes once it is ready.
+    queue_while_bootstrapping: bool,
+    /// Virtualenv the dependencies are installed into and the sidecar runs
+    /// from; empty uses `python_command` directly.
+    venv_path: String,
+    /// Uses the interpreter shipped in the app's resources instead of
+    /// `python_command`; dependencies go into its own site-packages.
+    use_bundled_python: bool,
+    /// Hugging Face cache the sidecar downloads models into, e.g. a folder
+    /// on a larger data drive. `None` keeps the default cache location.
+    model_cache_dir: Option<PathBuf>,
+    /// Runs pip when ASR dependencies are missing; when off, bootstrap
+    /// only reports them.
+    auto_install_deps: bool,
+    /// Requirement specifiers such as `torch==2.5.1` used instead of the
+    /// bare package name when that package has to be installed.
+    dependency_pins: Vec<String>,
+    /// How long the overlay lingers on "Done" after a dictation; 0 hides it
+    /// right away.
+    overlay_hide_delay_ms: u64,
+    remove_fillers: bool,
+    /// Whole words dropped from transcripts when `remove_fillers` is on.
+    filler_words: Vec<String>,
+    /// Turns spoken punctuation such as "comma" or "new line" into the
+    /// characters from `voice_commands`.
+    apply_voice_commands: bool,
+    voice_commands: Vec<VoiceCommand>,
+    /// Puts the previous clipboard back after pasting. When off, the
+    /// transcript stays on the clipboard and pasting skips the restore wait.
+    restore_clipboard: bool,
+    /// Moves each transcribed WAV into the app data dir with a `.json`
+    /// metadata file of the same stem instead of deleting it.
+    keep_recordings: bool,
+    /// Logs each transcript to `history.jsonl` for the history window.
+    save_history: bool,
+    /// Characters typed per burst with the Typing method; 0 types the whole
+    /// transcript at once.
+    type_chunk_size: usize,
+    /// Pause between typed chunks, giving slow editors time to keep up.
+    type_chunk_delay_ms: u64,
+    /// Mouse button that triggers dictation alongside the shortcut,
+    /// following `recording_mode`.
+    mouse_trigger: Option<MouseButton>,
+    capture_source: CaptureSource,
+    /// Sample rate requested from the microphone instead of its default,
+    /// e.g. 16000 to skip resampling in the sidecar. Ignored with a warning
+    /// when the device can't do it.
+    capture_sample_rate: Option<u32>,
+    /// Extra attempts at opening the capture stream when another app holds
+    /// the device exclusively, `RECORDER_RETRY_DELAY` apart.
+    recorder_retries: u32,
+    /// How often transcription progress ticks are emitted; 0 disables them.
+    progress_interval_ms: u64,
+    /// Shell command the transcript is piped through before delivery; its
+    /// stdout replaces the transcript. Failures fall back to the original.
+    post_process_command: Option<String>,
+    /// Lets a click on the overlay stop recording or cancel transcription.
+    /// When off, clicks pass through to whatever is underneath.
+    overlay_clickable: bool,
+    overlay_monitor: OverlayMonitor,
+    /// Overlay text per phase, e.g. "🎤 Speak now" while listening.
+    /// `{message}` is replaced by the built-in message, so error details
+    /// can be kept.
+    overlay_messages: HashMap<DictationPhase, String>,
+    /// When off the overlay window is never created, so status only reaches
+    /// the tray icon and the main window.
+    show_overlay: bool,
+    /// Keeps the settings window hidden on launch so the app starts silently
+    /// in the tray.
+    start_minimized: bool,
+    /// Decoding overrides passed to the sidecar; `None` keeps its defaults.
+    max_new_tokens: Option<u32>,
+    beam_size: Option<u32>,
+    temperature: Option<f32>,
+    theme: ThemePreference,
+    /// Appended to every delivered transcript, e.g. `\n` to start a new line
+    /// after each dictation. Supports `\n`, `\t` and `\\` escapes.
+    append_suffix: String,
+    /// Wraps every delivered transcript, e.g. `- {text}` to insert each
+    /// dictation as a Markdown bullet. Must contain `{text}`; takes the same
+    /// escapes as `append_suffix`.
+    paste_template: Option<String>,
+    /// Spoken phrase that starts a dictation hands-free; the recording then
+    /// stops itself after a pause. `None` keeps the microphone closed.
+    wake_word: Option<String>,
+}
+
+impl AppSettings {
+    fn restores_clipboard(&self) -> bool {
+        self.restore_clipboard && self.output_mode.restores_clipboard()
+    }
+}
+
+fn default_filler_words() -> Vec<String> {
+    ["um", "uh", "uhm", "erm", "er", "ah", "hmm"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_voice_commands() -> Vec<VoiceCommand> {
+    [
+        ("new line", "\\n"),
+        ("new paragraph", "\\n\\n"),
+        ("period", "."),
+        ("full stop", "."),
+        ("comma", ","),
+        ("question mark", "?"),
+        ("exclamation mark", "!"),
+        ("colon", ":"),
+        ("semicolon", ";"),
+    ]
+    .into_iter()
+    .map(|(phrase, output)| VoiceCommand {
+        phrase: phrase.to_string(),
+        output: output.to_string(),
+    })
+    .collect()
+}
+
+fn default_injection_denylist() -> Vec<String> {
+    [
+        "1password",
+        "bitwarden",
+        "keepass",
+        "keepassxc",
+        "keychain access",
+        "loginwindow",
+        "lockapp",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+fn default_injection_delay_ms() -> u64 {
+    if cfg!(target_os = "macos") {
+        200
+    } else if cfg!(target_os = "linux") {
+        180
+    } else {
+        140
+    }
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             shortcut: "Ctrl+Shift+Space".to_string(),
             recording_mode: RecordingMode::Hold,
+            queue_next_dictation: false,
             model: ModelOption::Qwen3Asr17b,
             language: "auto".to_string(),
             python_command: "python".to_string(),
             input_device: DEFAULT_INPUT_DEVICE.to_string(),
+            fallback_devices: Vec::new(),
+            injection_delay_ms: default_injection_delay_ms(),
+            output_mode: OutputMode::InjectAtCursor,
+            injection_method: InjectionMethod::Paste,
+            auto_inject: true,
+            app_profiles: Vec::new(),
+            silence_threshold: DEFAULT_SILENCE_THRESHOLD,
+            quiet_empty_transcripts: true,
+            compute_device: ComputeDevice::Auto,
+            audio_cues: false,
+            output_device: DEFAULT_OUTPUT_DEVICE.to_string(),
+            trim_threshold: DEFAULT_TRIM_THRESHOLD,
+            trim_margin_ms: DEFAULT_TRIM_MARGIN_MS,
+            chunk_length_secs: DEFAULT_CHUNK_LENGTH_SECS,
+            chunk_overlap_secs: DEFAULT_CHUNK_OVERLAP_SECS,
+            profiles: Vec::new(),
+            active_profile: None,
+            clipboard_fallback: true,
+            recording_format: RecordingFormat::Pcm16,
+            focus_guard: false,
+            unfocused_guard: true,
+            injection_denylist: default_injection_denylist(),
+            model_languages: HashMap::new(),
+            language_pin_after: 5,
+            auto_pin_language: false,
+            queue_while_bootstrapping: false,
+            venv_path: String::new(),
+            use_bundled_python: false,
+            model_cache_dir: None,
+            auto_install_deps: false,
+            dependency_pins: Vec::new(),
+            overlay_hide_delay_ms: 0,
+            remove_fillers: false,
+            filler_words: default_filler_words(),
+            apply_voice_commands: false,
+            voice_commands: default_voice_commands(),
+            restore_clipboard: true,
+            keep_recordings: false,
+            save_history: true,
+            type_chunk_size: 0,
+            type_chunk_delay_ms: 20,
+            mouse_trigger: None,
+            capture_source: CaptureSource::Microphone,
+            capture_sample_rate: None,
+            recorder_retries: 2,
+            progress_interval_ms: 1_000,
+            post_process_command: None,
+            overlay_clickable: false,
+            overlay_monitor: OverlayMonitor::FocusedWindow,
+            overlay_messages: HashMap::new(),
+            show_overlay: true,
+            start_minimized: false,
+            max_new_tokens: None,
+            beam_size: None,
+            temperature: None,
+            theme: ThemePreference::System,
+            append_suffix: String::new(),
+            paste_template: None,
+            wake_word: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 enum DictationPhase {
     Idle,
@@ -100,6 +614,83 @@ enum DictationPhase {
 struct DictationStatus {
     phase: DictationPhase,
     message: Option<String>,
+    /// Set only for updates from ASR setup, so the UI can track progress
+    /// without parsing `message`.
+    stage: Option<BootstrapStage>,
+    /// User template from `overlay_messages`, shown by the overlay in place
+    /// of its built-in label.
+    overlay_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum BootstrapStage {
+    CheckingPython,
+    InstallingDeps,
+    DownloadingModel,
+    WarmingUp,
+    Ready,
+    Failed,
+}
+
+impl BootstrapStage {
+    fn phase(self) -> DictationPhase {
+        match self {
+            BootstrapStage::Ready => DictationPhase::Idle,
+            BootstrapStage::Failed => DictationPhase::Error,
+            _ => DictationPhase::Bootstrapping,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum InstallStage {
+    Started,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LanguageSuggestion {
+    language: String,
+    detections: u32,
+    /// Already applied because `auto_pin_language` is on.
+    pinned: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallProgress {
+    stage: InstallStage,
+    message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SelectedInputDevice {
+    name: String,
+    fell_back: bool,
+}
+
+/// Capture format of an input device, so the UI can flag poor ASR input.
+/// The config fields are empty when the device could not be queried.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InputDeviceInfo {
+    name: String,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+    sample_format: Option<String>,
+}
+
+/// Snapshot of the runtime for windows that missed earlier status events.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeStatus {
+    phase: DictationPhase,
+    ready: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -113,1049 +704,6561 @@ enum WorkerCommand {
     Start,
     Stop,
     Toggle,
+    /// The capture stream writing to `path` died mid-recording.
+    StreamFailed {
+        path: PathBuf,
+        message: String,
+    },
+    /// Finalize any recording and stop the loop, acknowledging on `done`.
+    Shutdown {
+        done: Sender<()>,
+    },
 }
 
-struct RecorderSession {
-    stream: Stream,
-    writer: Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
-    path: PathBuf,
+/// Single JSON object the sidecar prints on stdout for each transcription.
+#[derive(Debug, Clone, Deserialize)]
+struct Transcription {
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    duration_ms: Option<u64>,
+    /// Set by the sidecar when the model ran fine but heard no words, as
+    /// opposed to producing no result at all.
+    #[serde(default)]
+    no_speech: bool,
+    /// Only filled in when the sidecar ran with `--timestamps`.
+    #[serde(default)]
+    segments: Vec<TranscriptSegment>,
 }
 
-impl RecorderSession {
-    fn finalize(self) -> Result<PathBuf, String> {
-        drop(self.stream);
+/// A stretch of the transcript with its position in the audio.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+struct TranscriptSegment {
+    text: String,
+    start_ms: u64,
+    end_ms: u64,
+}
 
-        if let Some(writer) = self
-            .writer
-            .lock()
-            .map_err(|_| "Failed to lock audio writer".to_string())?
-            .take()
-        {
-            writer
-                .finalize()
-                .map_err(|err| format!("Failed to finalize WAV file: {err}"))?;
-        }
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TimestampedTranscript {
+    text: String,
+    language: Option<String>,
+    segments: Vec<TranscriptSegment>,
+}
 
-        Ok(self.path)
-    }
+/// One model's run over a benchmark sample. `elapsed_ms` covers the whole
+/// sidecar call, including loading the model.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelBenchmark {
+    model: ModelOption,
+    transcript: Option<String>,
+    elapsed_ms: u64,
+    inference_ms: Option<u64>,
+    error: Option<String>,
 }
 
-struct AppRuntime {
-    settings: Mutex<AppSettings>,
-    phase: Mutex<RuntimePhase>,
-    ready: Mutex<bool>,
-    bootstrap_lock: Mutex<()>,
-    registered_shortcut: Mutex<String>,
-    worker_tx: Sender<WorkerCommand>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum TranscriptionStage {
+    Loading,
+    Decoding,
 }
 
-fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|err| format!("Failed to resolve app data dir: {err}"))?;
+/// Stage marker the sidecar prints, one JSON line each, as it moves from
+/// loading the model to decoding audio.
+#[derive(Debug, Clone, Deserialize)]
+struct TranscriptionStageLine {
+    stage: TranscriptionStage,
+}
 
-    fs::create_dir_all(&dir).map_err(|err| format!("Failed to create app data dir: {err}"))?;
-    Ok(dir.join(SETTINGS_FILE))
+/// Periodic tick while transcribing, so a long run doesn't look frozen.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptionProgress {
+    stage: Option<TranscriptionStage>,
+    elapsed_ms: u64,
 }
 
-fn load_settings(app: &AppHandle) -> AppSettings {
-    let Ok(path) = settings_path(app) else {
-        return AppSettings::default();
-    };
+#[derive(Debug, Clone, Deserialize)]
+struct ComputeProbe {
+    cuda: bool,
+    #[serde(default)]
+    cuda_device: Option<String>,
+    #[serde(default)]
+    mps: bool,
+}
 
-    let Ok(raw) = fs::read_to_string(path) else {
-        return AppSettings::default();
-    };
+#[derive(Debug, Clone, Deserialize)]
+struct SidecarEnvironment {
+    #[serde(default)]
+    torch_version: Option<String>,
+    #[serde(default)]
+    cuda_version: Option<String>,
+    #[serde(default)]
+    cuda_device: Option<String>,
+    device: String,
+    #[serde(default)]
+    cached_models: HashMap<String, bool>,
+}
 
-    serde_json::from_str::<AppSettings>(&raw).unwrap_or_default()
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelAvailability {
+    model: ModelOption,
+    id: String,
+    cached: bool,
 }
 
-fn save_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
-    let path = settings_path(app)?;
-    let serialized = serde_json::to_string_pretty(settings)
-        .map_err(|err| format!("Failed to serialize settings: {err}"))?;
-    fs::write(path, serialized).map_err(|err| format!("Failed to persist settings: {err}"))
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvironmentInfo {
+    torch_version: Option<String>,
+    cuda_version: Option<String>,
+    cuda_device: Option<String>,
+    device: String,
+    models: Vec<ModelAvailability>,
 }
 
-fn list_input_devices_internal() -> Result<Vec<String>, String> {
-    let host = cpal::default_host();
-    let mut devices = vec![DEFAULT_INPUT_DEVICE.to_string()];
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticCheck {
+    name: String,
+    passed: bool,
+    detail: String,
+}
 
-    let found = host
-        .input_devices()
-        .map_err(|err| format!("Failed to list input devices: {err}"))?;
+impl DiagnosticCheck {
+    fn from_result(name: &str, result: Result<String, impl Into<String>>) -> Self {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail.into()),
+        };
 
-    for device in found {
-        if let Ok(name) = device.name() {
-            if !name.trim().is_empty() && !devices.contains(&name) {
-                devices.push(name);
-            }
+        Self {
+            name: name.to_string(),
+            passed,
+            detail,
         }
     }
+}
 
-    Ok(devices)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DictationSummary {
+    transcript: String,
+    language: Option<String>,
+    word_count: usize,
+    recording_ms: u64,
+    transcription_ms: u64,
 }
 
-fn next_wav_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let mut cache_dir = app
-        .path()
-        .app_cache_dir()
-        .map_err(|err| format!("Failed to resolve app cache dir: {err}"))?;
+/// How a finished transcript reached the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum DeliveryMethod {
+    Paste,
+    Typing,
+    /// Copied only, by choice or because injection was blocked.
+    Clipboard,
+}
 
-    fs::create_dir_all(&cache_dir)
-        .map_err(|err| format!("Failed to create app cache dir: {err}"))?;
+impl DeliveryMethod {
+    fn for_settings(settings: &AppSettings) -> Self {
+        if !settings.auto_inject {
+            return Self::Clipboard;
+        }
 
-    let ts = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|err| format!("System time error: {err}"))?
-        .as_millis();
+        match settings.injection_method {
+            InjectionMethod::Paste => Self::Paste,
+            InjectionMethod::Typing => Self::Typing,
+        }
+    }
+}
 
-    cache_dir.push(format!("dictation-{ts}.wav"));
-    Ok(cache_dir)
+/// Outcome of the last delivery step, for history and stats views.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InjectionResult {
+    success: bool,
+    method: DeliveryMethod,
+    /// App the text was aimed at, when known.
+    target_app: Option<String>,
+    error: Option<String>,
 }
 
-fn write_i16_samples(
-    samples: &[i16],
-    writer: &Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
-) {
-    let Ok(mut guard) = writer.lock() else {
-        return;
-    };
+/// Written next to each kept recording as `<stem>.json`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordingMetadata {
+    model: String,
+    language: String,
+    detected_language: Option<String>,
+    duration_ms: u64,
+    sample_rate: u32,
+    transcript: String,
+}
 
-    let Some(writer) = guard.as_mut() else {
-        return;
-    };
+/// Broad failure class a command error falls into, so the UI can branch on
+/// it instead of matching message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ErrorCode {
+    PythonMissing,
+    DependencyMissing,
+    DeviceNotFound,
+    TranscriptEmpty,
+    InjectionFailed,
+    ShortcutInUse,
+    NotReady,
+    Cancelled,
+    Unknown,
+}
 
-    for &sample in samples {
-        let _ = writer.write_sample(sample);
+/// Error returned by commands. Failures with a known cause set their code
+/// where they happen; plain `String` errors arrive as `Unknown`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl AppError {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
     }
 }
 
-fn write_u16_samples(
-    samples: &[u16],
-    writer: &Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
-) {
-    let Ok(mut guard) = writer.lock() else {
-        return;
-    };
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
 
-    let Some(writer) = guard.as_mut() else {
-        return;
-    };
+impl std::error::Error for AppError {}
 
-    for &sample in samples {
-        let centered = (sample as i32 - 32_768) as i16;
-        let _ = writer.write_sample(centered);
+/// Lets code that still reports `String` errors pass typed ones along.
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.message
     }
 }
 
-fn write_f32_samples(
-    samples: &[f32],
-    writer: &Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
-) {
-    let Ok(mut guard) = writer.lock() else {
-        return;
-    };
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Unknown, message)
+    }
+}
 
-    let Some(writer) = guard.as_mut() else {
-        return;
-    };
+/// The binding the runtime is actually listening for, after normalization.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisteredShortcut {
+    shortcut: String,
+    recording_mode: RecordingMode,
+}
 
-    for &sample in samples {
-        let clamped = sample.clamp(-1.0, 1.0);
-        let s = (clamped * i16::MAX as f32) as i16;
-        let _ = writer.write_sample(s);
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordingsUsage {
+    /// Bytes taken by kept recordings and their metadata files.
+    total_bytes: u64,
+    recording_count: usize,
+}
+
+/// One line of the transcript history log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryEntry {
+    /// Unix time in milliseconds; also identifies the entry.
+    timestamp_ms: u64,
+    transcript: String,
+    model: String,
+}
+
+/// Levels of a short test recording, as `0.0..=1.0` of full scale.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MicrophoneTest {
+    peak: f32,
+    rms: f32,
+    /// Below the silence threshold, so a real dictation would be skipped.
+    silent: bool,
+}
+
+struct RecorderSession {
+    stream: Stream,
+    writer: Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
+    path: PathBuf,
+    started_at: Instant,
+    /// Foreground window when recording started, for the focus guard.
+    target: FocusTarget,
+}
+
+impl RecorderSession {
+    /// Audio written so far; zero until the device delivers its first
+    /// buffer. `None` when the writer can't be inspected.
+    fn captured(&self) -> Option<Duration> {
+        let writer = self.writer.lock().ok()?;
+        let Some(writer) = writer.as_ref() else {
+            return Some(Duration::ZERO);
+        };
+
+        let spec = writer.spec();
+        let frames = writer.len() / u32::from(spec.channels.max(1));
+        Some(Duration::from_secs_f64(
+            f64::from(frames) / f64::from(spec.sample_rate.max(1)),
+        ))
+    }
+
+    fn finalize(self) -> Result<PathBuf, String> {
+        drop(self.stream);
+
+        if let Some(writer) = self
+            .writer
+            .lock()
+            .map_err(|_| "Failed to lock audio writer".to_string())?
+            .take()
+        {
+            writer
+                .finalize()
+                .map_err(|err| format!("Failed to finalize WAV file: {err}"))?;
+        }
+
+        Ok(self.path)
     }
 }
 
-fn resolve_input_device(settings: &AppSettings) -> Result<cpal::Device, String> {
-    let host = cpal::default_host();
+#[derive(Debug, Clone, Copy)]
+enum TimingKind {
+    RecorderStart,
+    Transcription,
+    Inference,
+}
+
+#[derive(Default)]
+struct PerformanceHistory {
+    recorder_start: VecDeque<Duration>,
+    transcription: VecDeque<Duration>,
+    inference: VecDeque<Duration>,
+}
+
+impl PerformanceHistory {
+    fn record(&mut self, kind: TimingKind, elapsed: Duration) {
+        let samples = match kind {
+            TimingKind::RecorderStart => &mut self.recorder_start,
+            TimingKind::Transcription => &mut self.transcription,
+            TimingKind::Inference => &mut self.inference,
+        };
 
-    if settings.input_device == DEFAULT_INPUT_DEVICE {
-        return host
-            .default_input_device()
-            .ok_or_else(|| "No default microphone found".to_string());
+        if samples.len() >= PERFORMANCE_HISTORY_LEN {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed);
     }
+}
 
-    let devices = host
-        .input_devices()
-        .map_err(|err| format!("Failed to list input devices: {err}"))?;
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DurationStats {
+    samples: usize,
+    average_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+}
 
-    for device in devices {
-        if let Ok(name) = device.name() {
-            if name == settings.input_device {
-                return Ok(device);
-            }
+impl DurationStats {
+    fn from_samples(samples: &VecDeque<Duration>) -> Option<Self> {
+        let min = samples.iter().min()?;
+        let max = samples.iter().max()?;
+        let total: Duration = samples.iter().sum();
+
+        Some(Self {
+            samples: samples.len(),
+            average_ms: total.as_secs_f64() * 1000.0 / samples.len() as f64,
+            min_ms: min.as_secs_f64() * 1000.0,
+            max_ms: max.as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PerformanceStats {
+    model: String,
+    recorder_start: Option<DurationStats>,
+    transcription: Option<DurationStats>,
+    inference: Option<DurationStats>,
+}
+
+/// A transcript held for review, with the window it was dictated into.
+struct PendingTranscript {
+    text: String,
+    target: FocusTarget,
+}
+
+struct AppRuntime {
+    settings: Mutex<AppSettings>,
+    phase: Mutex<RuntimePhase>,
+    ready: Mutex<bool>,
+    enabled: Mutex<bool>,
+    enabled_menu_item: Mutex<Option<CheckMenuItem<Wry>>>,
+    last_activation: Mutex<Option<Instant>>,
+    modifier_watch_generation: Mutex<u64>,
+    mouse_watch_generation: Mutex<u64>,
+    wake_watch_generation: Mutex<u64>,
+    overlay_hide_generation: Mutex<u64>,
+    /// Transcripts waiting in the review window for confirm or discard,
+    /// oldest first; the window shows the front one.
+    pending_transcripts: Mutex<VecDeque<PendingTranscript>>,
+    /// Running transcription sidecar; `abort_transcription` takes and kills it.
+    transcription_child: Mutex<Option<Child>>,
+    /// Last stage the running sidecar reported.
+    transcription_stage: Mutex<Option<TranscriptionStage>>,
+    bootstrap_lock: Mutex<()>,
+    /// Bumped for every requested bootstrap; older runs bail out at their
+    /// next step instead of finishing redundant work.
+    bootstrap_generation: Mutex<u64>,
+    registered_shortcut: Mutex<String>,
+    performance: Mutex<PerformanceHistory>,
+    /// Writable directory for recordings, resolved during bootstrap.
+    recordings_dir: Mutex<Option<PathBuf>>,
+    /// Appended to recording names so two sessions started within the same
+    /// millisecond never share a file.
+    recording_counter: Mutex<u64>,
+    /// Start requested while transcribing, run by the worker once the
+    /// transcription is done. Only set with `queue_next_dictation`.
+    queued_start: Mutex<bool>,
+    /// Language the sidecar detected last and how many dictations in a row
+    /// reported it, tracked while `language` is "auto".
+    language_streak: Mutex<Option<(String, u32)>>,
+    worker_tx: Sender<WorkerCommand>,
+}
+
+/// Uses `resolved`, or `~/.delulu/<subdir>` when the Tauri path APIs fail,
+/// as they do on minimal Linux setups without XDG variables.
+fn dir_or_fallback(label: &str, resolved: tauri::Result<PathBuf>, subdir: &str) -> PathBuf {
+    match resolved {
+        Ok(dir) => dir,
+        Err(err) => {
+            let home = std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(PathBuf::from)
+                .unwrap_or_else(std::env::temp_dir);
+            let fallback = home.join(FALLBACK_APP_DIR).join(subdir);
+            eprintln!(
+                "failed to resolve the {label} ({err}), using {}",
+                fallback.display()
+            );
+            fallback
         }
     }
+}
 
-    host.default_input_device().ok_or_else(|| {
-        format!(
-            "Configured microphone '{}' not found and no default device available",
-            settings.input_device
-        )
-    })
+fn app_data_dir(app: &AppHandle) -> PathBuf {
+    dir_or_fallback("app data dir", app.path().app_data_dir(), "data")
+}
+
+fn app_cache_dir(app: &AppHandle) -> PathBuf {
+    dir_or_fallback("app cache dir", app.path().app_cache_dir(), "cache")
 }
 
-fn start_recorder(app: &AppHandle, settings: &AppSettings) -> Result<RecorderSession, String> {
-    let input_device = resolve_input_device(settings)?;
+/// `app_data_dir`, created if missing.
+fn ensure_app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_data_dir(app);
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create app data dir {}: {err}", dir.display()))?;
+    Ok(dir)
+}
 
-    let supported = input_device
-        .default_input_config()
-        .map_err(|err| format!("Failed to read input config: {err}"))?;
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(ensure_app_data_dir(app)?.join(SETTINGS_FILE))
+}
 
-    let wav_path = next_wav_path(app)?;
-    let spec = WavSpec {
-        channels: supported.channels(),
-        sample_rate: supported.sample_rate().0,
-        bits_per_sample: 16,
-        sample_format: WavSampleFormat::Int,
+fn load_settings(app: &AppHandle) -> AppSettings {
+    let path = match settings_path(app) {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("{err}, using default settings");
+            return AppSettings::default();
+        }
     };
 
-    let writer = WavWriter::create(&wav_path, spec)
-        .map_err(|err| format!("Failed to create WAV writer: {err}"))?;
-    let writer = Arc::new(Mutex::new(Some(writer)));
+    let Ok(raw) = fs::read_to_string(path) else {
+        return AppSettings::default();
+    };
 
-    let stream_config: StreamConfig = supported.clone().into();
-    let err_fn = |err| {
-        eprintln!("audio input stream error: {err}");
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&raw) else {
+        eprintln!("settings file is not valid JSON, using defaults");
+        return AppSettings::default();
     };
 
-    let stream = match supported.sample_format() {
-        SampleFormat::I16 => {
-            let writer = writer.clone();
-            input_device
-                .build_input_stream(
-                    &stream_config,
-                    move |data: &[i16], _| write_i16_samples(data, &writer),
-                    err_fn,
-                    None,
-                )
-                .map_err(|err| format!("Failed to build i16 input stream: {err}"))?
-        }
-        SampleFormat::U16 => {
-            let writer = writer.clone();
-            input_device
-                .build_input_stream(
-                    &stream_config,
-                    move |data: &[u16], _| write_u16_samples(data, &writer),
-                    err_fn,
-                    None,
-                )
-                .map_err(|err| format!("Failed to build u16 input stream: {err}"))?
-        }
-        SampleFormat::F32 => {
-            let writer = writer.clone();
-            input_device
-                .build_input_stream(
-                    &stream_config,
-                    move |data: &[f32], _| write_f32_samples(data, &writer),
-                    err_fn,
-                    None,
-                )
-                .map_err(|err| format!("Failed to build f32 input stream: {err}"))?
-        }
-        other => {
-            return Err(format!("Unsupported sample format: {other:?}"));
+    let (settings, recovered) = match serde_json::from_value::<AppSettings>(value.clone()) {
+        Ok(settings) => (settings, false),
+        Err(err) => {
+            eprintln!("settings did not match the current schema ({err}), recovering fields");
+            (recover_settings(&value), true)
         }
     };
 
-    stream
-        .play()
-        .map_err(|err| format!("Failed to start audio capture: {err}"))?;
+    if !recovered && settings.version >= SETTINGS_VERSION {
+        return settings;
+    }
 
-    Ok(RecorderSession {
-        stream,
-        writer,
-        path: wav_path,
-    })
+    let settings = migrate_settings(settings);
+    if let Err(err) = save_settings(app, &settings) {
+        eprintln!("failed to persist migrated settings: {err}");
+    }
+
+    settings
 }
 
-fn resolve_transcriber_script(app: &AppHandle) -> Result<PathBuf, String> {
-    let mut candidates = Vec::new();
+/// Rebuilds settings from an older or partially invalid file, keeping every
+/// field that still deserializes and defaulting the rest.
+fn recover_settings(raw: &serde_json::Value) -> AppSettings {
+    let defaults = AppSettings::default();
 
-    if let Ok(resource_dir) = app.path().resource_dir() {
-        candidates.push(resource_dir.join("python").join("qwen_asr_transcribe.py"));
-    }
+    let Some(fields) = raw.as_object() else {
+        return defaults;
+    };
 
-    candidates.push(
-        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("python")
-            .join("qwen_asr_transcribe.py"),
-    );
+    let Ok(serde_json::Value::Object(mut merged)) = serde_json::to_value(&defaults) else {
+        return defaults;
+    };
 
-    if let Ok(current_dir) = std::env::current_dir() {
-        candidates.push(
-            current_dir
-                .join("src-tauri")
-                .join("python")
-                .join("qwen_asr_transcribe.py"),
-        );
+    let mut recovered = defaults;
+    for (key, value) in fields {
+        if !merged.contains_key(key) {
+            continue;
+        }
+
+        let previous = merged.insert(key.clone(), value.clone());
+        match serde_json::from_value::<AppSettings>(serde_json::Value::Object(merged.clone())) {
+            Ok(settings) => recovered = settings,
+            Err(_) => {
+                if let Some(previous) = previous {
+                    merged.insert(key.clone(), previous);
+                }
+            }
+        }
     }
 
-    candidates
-        .into_iter()
-        .find(|path| path.exists())
-        .ok_or_else(|| "Could not locate qwen_asr_transcribe.py".to_string())
+    recovered
 }
 
-fn command_error(prefix: &str, stderr: &[u8]) -> String {
-    let detail = String::from_utf8_lossy(stderr).trim().to_string();
-    if detail.is_empty() {
-        prefix.to_string()
-    } else {
-        format!("{prefix}: {detail}")
-    }
+fn migrate_settings(mut settings: AppSettings) -> AppSettings {
+    // Version 0 predates the version field; its fields carry over unchanged.
+    settings.version = SETTINGS_VERSION;
+    settings
 }
 
-fn configure_child_process(command: &mut Command) {
-    #[cfg(windows)]
-    {
-        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
-        command.creation_flags(CREATE_NO_WINDOW);
-    }
+fn save_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let serialized = serde_json::to_string_pretty(settings)
+        .map_err(|err| format!("Failed to serialize settings: {err}"))?;
+    fs::write(path, serialized).map_err(|err| format!("Failed to persist settings: {err}"))
 }
 
-fn ensure_python_binary(settings: &AppSettings) -> Result<(), String> {
-    let mut command = Command::new(&settings.python_command);
-    command.arg("--version");
-    configure_child_process(&mut command);
+fn list_input_devices_internal() -> Result<Vec<String>, AppError> {
+    let host = cpal::default_host();
+    let mut devices = vec![DEFAULT_INPUT_DEVICE.to_string()];
 
-    let output = command.output().map_err(|err| {
-        format!(
-            "Python command '{}' failed to start: {err}",
-            settings.python_command
+    let found = host.input_devices().map_err(|err| {
+        AppError::new(
+            ErrorCode::DeviceNotFound,
+            format!("Failed to list input devices: {err}"),
         )
     })?;
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        Err(command_error(
-            &format!("Python command '{}' is not usable", settings.python_command),
-            &output.stderr,
-        ))
+    for device in found {
+        if let Ok(name) = device.name() {
+            if !name.trim().is_empty() && !devices.contains(&name) {
+                devices.push(name);
+            }
+        }
     }
-}
 
-fn ensure_python_dependencies(settings: &AppSettings) -> Result<(), String> {
-    let mut check_command = Command::new(&settings.python_command);
-    check_command.args(["-c", "import qwen_asr, torch, torchvision"]);
-    configure_child_process(&mut check_command);
+    Ok(devices)
+}
 
-    let check = check_command.output().map_err(|err| {
-        format!(
-            "Dependency check failed for '{}': {err}",
-            settings.python_command
-        )
-    })?;
+/// Whether the configured input device is plugged in right now. The default
+/// device counts as present whenever the host has one.
+fn validate_input_device_internal(state: &Arc<AppRuntime>) -> Result<bool, AppError> {
+    let input_device = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .input_device
+        .clone();
 
-    if check.status.success() {
-        return Ok(());
+    if input_device == DEFAULT_INPUT_DEVICE {
+        return Ok(cpal::default_host().default_input_device().is_some());
     }
 
-    let mut install_command = Command::new(&settings.python_command);
-    install_command.args([
-        "-m",
-        "pip",
-        "install",
-        "-U",
-        "qwen-asr",
-        "torch",
-        "torchvision",
-    ]);
-    configure_child_process(&mut install_command);
+    Ok(list_input_devices_internal()?.contains(&input_device))
+}
 
-    let install = install_command
-        .output()
-        .map_err(|err| format!("Failed launching pip installer: {err}"))?;
+fn list_output_devices_internal() -> Result<Vec<String>, String> {
+    let host = cpal::default_host();
+    let mut devices = vec![DEFAULT_OUTPUT_DEVICE.to_string()];
 
-    if install.status.success() {
-        Ok(())
-    } else {
-        Err(command_error(
-            "Auto-install failed (pip install -U qwen-asr torch torchvision)",
-            &install.stderr,
-        ))
-    }
-}
+    let found = host
+        .output_devices()
+        .map_err(|err| format!("Failed to list output devices: {err}"))?;
 
-fn warmup_selected_model(settings: &AppSettings, app: &AppHandle) -> Result<(), String> {
-    let script_path = resolve_transcriber_script(app)?;
+    for device in found {
+        if let Ok(name) = device.name() {
+            if !name.trim().is_empty() && !devices.contains(&name) {
+                devices.push(name);
+            }
+        }
+    }
 
-    let mut command = Command::new(&settings.python_command);
-    command
-        .arg(script_path)
-        .arg("--warmup")
-        .arg("--model")
-        .arg(settings.model.as_hf_id())
-        .arg("--language")
-        .arg(&settings.language);
-    configure_child_process(&mut command);
+    Ok(devices)
+}
 
-    let output = command
-        .output()
-        .map_err(|err| format!("Failed launching model warmup: {err}"))?;
+fn describe_input_device(name: String, device: Option<&cpal::Device>) -> InputDeviceInfo {
+    let config = device.and_then(|device| {
+        device.default_input_config().ok().or_else(|| {
+            device
+                .supported_input_configs()
+                .ok()?
+                .find(|range| is_capturable_format(range.sample_format()))
+                .map(|range| range.with_max_sample_rate())
+        })
+    });
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        Err(command_error("Model warmup failed", &output.stderr))
+    InputDeviceInfo {
+        name,
+        sample_rate: config.as_ref().map(|config| config.sample_rate().0),
+        channels: config.as_ref().map(|config| config.channels()),
+        sample_format: config.map(|config| config.sample_format().to_string()),
     }
 }
 
-fn bootstrap_asr_runtime(
-    app: &AppHandle,
-    state: &Arc<AppRuntime>,
-    settings: AppSettings,
-) -> Result<(), String> {
-    let _bootstrap_guard = state
-        .bootstrap_lock
-        .lock()
-        .map_err(|_| "Failed to lock bootstrap state".to_string())?;
-
-    let _ = set_runtime_ready(state, false);
-    emit_status(
-        app,
-        DictationPhase::Bootstrapping,
-        Some("Checking Python runtime...".to_string()),
-    );
+fn list_input_device_details_internal() -> Result<Vec<InputDeviceInfo>, AppError> {
+    let host = cpal::default_host();
+    let mut devices = vec![describe_input_device(
+        DEFAULT_INPUT_DEVICE.to_string(),
+        host.default_input_device().as_ref(),
+    )];
+
+    let found = host.input_devices().map_err(|err| {
+        AppError::new(
+            ErrorCode::DeviceNotFound,
+            format!("Failed to list input devices: {err}"),
+        )
+    })?;
 
-    ensure_python_binary(&settings)?;
+    for device in found {
+        if let Ok(name) = device.name() {
+            if !name.trim().is_empty() && !devices.iter().any(|info| info.name == name) {
+                devices.push(describe_input_device(name, Some(&device)));
+            }
+        }
+    }
 
-    emit_status(
-        app,
-        DictationPhase::Bootstrapping,
-        Some("Ensuring ASR dependencies are installed...".to_string()),
-    );
-    ensure_python_dependencies(&settings)?;
+    Ok(devices)
+}
 
-    emit_status(
-        app,
-        DictationPhase::Bootstrapping,
-        Some("Preparing selected model (first run may download)...".to_string()),
-    );
-    warmup_selected_model(&settings, app)?;
+/// Creates `dir` and proves it accepts writes.
+fn ensure_writable_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
 
-    let _ = set_runtime_ready(state, true);
-    emit_status(app, DictationPhase::Idle, Some("Ready".to_string()));
+    let probe = dir.join(".write-test");
+    fs::write(&probe, b"ok").map_err(|err| err.to_string())?;
+    let _ = fs::remove_file(&probe);
     Ok(())
 }
 
-fn spawn_bootstrap_task(app: AppHandle, state: Arc<AppRuntime>, settings: AppSettings) {
-    thread::spawn(move || {
-        if let Err(err) = bootstrap_asr_runtime(&app, &state, settings) {
-            let _ = set_runtime_ready(&state, false);
-            emit_status(&app, DictationPhase::Error, Some(err));
-        }
-    });
+fn fallback_recordings_dir() -> PathBuf {
+    std::env::temp_dir().join(FALLBACK_RECORDINGS_DIR)
 }
 
-fn transcribe_audio(
-    settings: &AppSettings,
-    app: &AppHandle,
-    audio_path: &Path,
-) -> Result<String, String> {
-    let script_path = resolve_transcriber_script(app)?;
-
-    let mut command = Command::new(&settings.python_command);
-    command
-        .arg(script_path)
-        .arg("--audio")
-        .arg(audio_path)
-        .arg("--model")
-        .arg(settings.model.as_hf_id())
-        .arg("--language")
-        .arg(&settings.language);
-    configure_child_process(&mut command);
-
-    let output = command.output().map_err(|err| {
-        format!(
-            "Failed to launch Python process '{}': {err}",
-            settings.python_command
-        )
-    })?;
+/// Picks where recordings are written: the app cache dir, or the OS temp dir
+/// when the cache disk is full or read-only.
+fn resolve_recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_cache_dir(app);
+    let cache_err = match ensure_writable_dir(&dir) {
+        Ok(()) => return Ok(dir),
+        Err(err) => format!("{} is not writable ({err})", dir.display()),
+    };
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ASR sidecar failed: {stderr}"));
+    let fallback = fallback_recordings_dir();
+    match ensure_writable_dir(&fallback) {
+        Ok(()) => {
+            eprintln!("recording to {} because {cache_err}", fallback.display());
+            Ok(fallback)
+        }
+        Err(err) => Err(format!(
+            "Cannot store recordings: {cache_err}, and {} is not writable either ({err}). Free up disk space or check folder permissions.",
+            fallback.display()
+        )),
     }
+}
+
+fn next_wav_path(app: &AppHandle, state: &Arc<AppRuntime>) -> Result<PathBuf, String> {
+    let resolved = state
+        .recordings_dir
+        .lock()
+        .map_err(|_| "Failed to lock recordings dir".to_string())?
+        .clone();
+    let mut dir = match resolved {
+        Some(dir) => dir,
+        None => resolve_recordings_dir(app)?,
+    };
 
-    let stdout = String::from_utf8(output.stdout)
-        .map_err(|err| format!("Invalid UTF-8 from sidecar: {err}"))?;
-    let transcript = stdout.trim().to_string();
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create recordings dir {}: {err}", dir.display()))?;
 
-    if transcript.is_empty() {
-        return Err("ASR returned empty transcript".to_string());
-    }
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| format!("System time error: {err}"))?
+        .as_millis();
+    let sequence = {
+        let mut counter = state
+            .recording_counter
+            .lock()
+            .map_err(|_| "Failed to lock recording counter".to_string())?;
+        *counter += 1;
+        *counter
+    };
 
-    Ok(transcript)
+    dir.push(format!(
+        "{RECORDING_PREFIX}{ts}-{sequence}.{RECORDING_EXTENSION}"
+    ));
+    Ok(dir)
 }
 
-fn inject_text_at_cursor(transcript: &str) -> Result<(), String> {
-    if transcript.is_empty() {
-        return Ok(());
-    }
-
-    let mut clipboard = Clipboard::new().map_err(|err| format!("Clipboard init failed: {err}"))?;
-    let previous_clipboard = clipboard.get_text().ok();
-    clipboard
-        .set_text(transcript.to_string())
-        .map_err(|err| format!("Failed to write transcript to clipboard: {err}"))?;
+fn is_recording_file(path: &Path) -> bool {
+    let has_prefix = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(RECORDING_PREFIX));
+    let has_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(RECORDING_EXTENSION));
+
+    has_prefix && has_extension
+}
 
-    let mut enigo = Enigo::new(&Settings::default())
-        .map_err(|err| format!("Input automation init failed: {err}"))?;
+fn cleanup_stale_recordings(app: &AppHandle) {
+    let dirs = [app_cache_dir(app), fallback_recordings_dir()];
 
-    enigo
-        .key(Key::Control, Press)
-        .and_then(|_| enigo.key(Key::Unicode('v'), Click))
-        .and_then(|_| enigo.key(Key::Control, Release))
-        .map_err(|err| format!("Failed to paste transcript: {err}"))?;
+    let now = SystemTime::now();
+    for entry in dirs
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+    {
+        let path = entry.path();
+        if !path.is_file() || !is_recording_file(&path) {
+            continue;
+        }
 
-    thread::sleep(Duration::from_millis(140));
+        let is_stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age >= STALE_RECORDING_AGE);
 
-    if let Some(previous) = previous_clipboard {
-        let _ = clipboard.set_text(previous);
+        if is_stale {
+            if let Err(err) = fs::remove_file(&path) {
+                eprintln!("failed to remove stale recording {}: {err}", path.display());
+            }
+        }
     }
-
-    Ok(())
-}
-
-fn show_settings_window(app: &AppHandle) -> Result<(), String> {
-    let window = app
-        .get_webview_window("main")
-        .ok_or_else(|| "Main window not found".to_string())?;
-    window
-        .show()
-        .map_err(|err| format!("Failed to show main window: {err}"))?;
-    window
-        .set_focus()
-        .map_err(|err| format!("Failed to focus main window: {err}"))
 }
 
-fn hide_settings_window(app: &AppHandle) -> Result<(), String> {
-    let window = app
-        .get_webview_window("main")
-        .ok_or_else(|| "Main window not found".to_string())?;
-    window
-        .hide()
-        .map_err(|err| format!("Failed to hide main window: {err}"))
+fn kept_recordings_dir(app: &AppHandle) -> PathBuf {
+    app_data_dir(app).join(KEPT_RECORDINGS_DIR)
 }
 
-fn ensure_overlay_window(app: &AppHandle) -> Result<(), String> {
-    if app.get_webview_window(OVERLAY_LABEL).is_some() {
-        return Ok(());
+/// Moves a transcribed recording out of the scratch dir, where stale
+/// cleanup would delete it, and writes its metadata alongside.
+fn keep_recording(
+    app: &AppHandle,
+    audio_path: &Path,
+    metadata: &RecordingMetadata,
+) -> Result<PathBuf, String> {
+    let dir = kept_recordings_dir(app);
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create recordings dir {}: {err}", dir.display()))?;
+
+    let file_name = audio_path
+        .file_name()
+        .ok_or_else(|| format!("Invalid recording path {}", audio_path.display()))?;
+    let kept_path = dir.join(file_name);
+
+    // Renaming fails across filesystems, e.g. from the temp fallback dir.
+    if fs::rename(audio_path, &kept_path).is_err() {
+        fs::copy(audio_path, &kept_path)
+            .map_err(|err| format!("Failed to keep recording {}: {err}", kept_path.display()))?;
+        let _ = fs::remove_file(audio_path);
     }
 
-    let _window = WebviewWindowBuilder::new(
-        app,
-        OVERLAY_LABEL,
-        WebviewUrl::App("index.html?overlay=1".into()),
-    )
-    .title("Dictation Overlay")
-    .inner_size(280.0, 72.0)
-    .resizable(false)
-    .decorations(false)
-    .always_on_top(true)
-    .focusable(false)
-    .skip_taskbar(true)
-    .visible(false)
-    .build()
-    .map_err(|err| format!("Failed to create overlay window: {err}"))?;
+    let raw = serde_json::to_string_pretty(metadata)
+        .map_err(|err| format!("Failed to serialize recording metadata: {err}"))?;
+    let metadata_path = kept_path.with_extension("json");
+    fs::write(&metadata_path, raw).map_err(|err| {
+        format!(
+            "Failed to write recording metadata {}: {err}",
+            metadata_path.display()
+        )
+    })?;
 
-    Ok(())
+    Ok(kept_path)
 }
 
-fn place_overlay_bottom_center(app: &AppHandle) {
-    let Some(window) = app.get_webview_window(OVERLAY_LABEL) else {
-        return;
+/// Kept recordings paired with their modification time, newest first.
+fn list_kept_recordings(app: &AppHandle) -> Vec<(PathBuf, SystemTime)> {
+    let Ok(entries) = fs::read_dir(kept_recordings_dir(app)) else {
+        return Vec::new();
     };
 
-    let monitor = window
-        .current_monitor()
-        .ok()
+    let mut recordings: Vec<(PathBuf, SystemTime)> = entries
         .flatten()
-        .or_else(|| window.primary_monitor().ok().flatten());
-
-    let Some(monitor) = monitor else {
-        return;
-    };
-
-    let work_area = monitor.work_area();
-    let overlay_size = match window.inner_size() {
-        Ok(size) => size,
-        Err(_) => return,
-    };
-
-    let x = work_area.position.x + ((work_area.size.width as i32 - overlay_size.width as i32) / 2);
-    let y = work_area.position.y + ((work_area.size.height as f32 * 0.90) as i32)
-        - (overlay_size.height as i32 / 2);
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_recording_file(path))
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(UNIX_EPOCH);
+            (path, modified)
+        })
+        .collect();
+    recordings.sort_by_key(|recording| std::cmp::Reverse(recording.1));
+    recordings
+}
 
-    let _ = window.set_position(Position::Physical(PhysicalPosition::new(x, y)));
+fn recordings_usage_internal(app: &AppHandle) -> RecordingsUsage {
+    let recordings = list_kept_recordings(app);
+    let total_bytes = recordings
+        .iter()
+        .flat_map(|(path, _)| [path.clone(), path.with_extension("json")])
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    RecordingsUsage {
+        total_bytes,
+        recording_count: recordings.len(),
+    }
 }
 
-fn emit_status(app: &AppHandle, phase: DictationPhase, message: Option<String>) {
-    let payload = DictationStatus {
-        phase: phase.clone(),
-        message,
-    };
+/// Deletes kept recordings beyond the newest `keep_last` or older than
+/// `older_than_days`, along with their metadata. Returns how many went.
+fn prune_recordings_internal(
+    app: &AppHandle,
+    keep_last: Option<usize>,
+    older_than_days: Option<u64>,
+) -> Result<usize, String> {
+    if keep_last.is_none() && older_than_days.is_none() {
+        return Err("Choose how many recordings to keep or a maximum age".to_string());
+    }
 
-    let _ = app.emit(DICTATION_EVENT, payload.clone());
+    let max_age = older_than_days.map(|days| Duration::from_secs(days * 24 * 60 * 60));
+    let now = SystemTime::now();
+    let mut removed = 0;
 
-    if let Some(overlay) = app.get_webview_window(OVERLAY_LABEL) {
-        let _ = overlay.emit(DICTATION_EVENT, payload);
+    for (index, (path, modified)) in list_kept_recordings(app).into_iter().enumerate() {
+        let beyond_limit = keep_last.is_some_and(|keep_last| index >= keep_last);
+        let too_old = max_age
+            .is_some_and(|max_age| now.duration_since(modified).is_ok_and(|age| age >= max_age));
+        if !beyond_limit && !too_old {
+            continue;
+        }
 
-        match phase {
-            DictationPhase::Idle => {
-                let _ = overlay.hide();
-            }
-            _ => {
-                place_overlay_bottom_center(app);
-                let _ = overlay.show();
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                let _ = fs::remove_file(path.with_extension("json"));
+                removed += 1;
             }
+            Err(err) => eprintln!("failed to prune recording {}: {err}", path.display()),
         }
     }
-}
 
-fn set_phase(state: &Arc<AppRuntime>, phase: RuntimePhase) -> Result<(), String> {
-    *state
-        .phase
-        .lock()
-        .map_err(|_| "Failed to lock runtime phase".to_string())? = phase;
-    Ok(())
+    Ok(removed)
 }
 
-fn current_phase(state: &Arc<AppRuntime>) -> Result<RuntimePhase, String> {
-    state
-        .phase
-        .lock()
-        .map(|phase| *phase)
-        .map_err(|_| "Failed to lock runtime phase".to_string())
+fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(ensure_app_data_dir(app)?.join(HISTORY_FILE))
 }
 
-fn set_runtime_ready(state: &Arc<AppRuntime>, ready: bool) -> Result<(), String> {
-    *state
-        .ready
-        .lock()
-        .map_err(|_| "Failed to lock runtime readiness".to_string())? = ready;
+fn append_history(app: &AppHandle, settings: &AppSettings, transcript: &str) -> Result<(), String> {
+    let entry = HistoryEntry {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default(),
+        transcript: transcript.to_string(),
+        model: settings.model.as_hf_id().to_string(),
+    };
+    let line = serde_json::to_string(&entry)
+        .map_err(|err| format!("Failed to serialize history entry: {err}"))?;
+
+    let path = history_path(app)?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| format!("Failed to open history {}: {err}", path.display()))?;
+    writeln!(file, "{line}").map_err(|err| format!("Failed to write history: {err}"))?;
+
+    let size = file
+        .metadata()
+        .map_err(|err| format!("Failed to read history {}: {err}", path.display()))?
+        .len();
+    if size > MAX_HISTORY_BYTES {
+        rotate_history(&path)?;
+    }
     Ok(())
 }
 
-fn is_runtime_ready(state: &Arc<AppRuntime>) -> Result<bool, String> {
-    state
-        .ready
-        .lock()
-        .map(|ready| *ready)
-        .map_err(|_| "Failed to lock runtime readiness".to_string())
+/// Rewrites the log with only its newest entries, so reading it stays cheap.
+fn rotate_history(path: &Path) -> Result<(), String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read history {}: {err}", path.display()))?;
+    let kept = newest_history_lines(&raw, (MAX_HISTORY_BYTES / 2) as usize);
+
+    let temp_path = path.with_extension("jsonl.tmp");
+    fs::write(&temp_path, kept)
+        .map_err(|err| format!("Failed to write history {}: {err}", temp_path.display()))?;
+    fs::rename(&temp_path, path)
+        .map_err(|err| format!("Failed to replace history {}: {err}", path.display()))
 }
 
-fn worker_start(app: &AppHandle, state: &Arc<AppRuntime>, active: &mut Option<RecorderSession>) {
-    if active.is_some() {
-        return;
-    }
-
-    match current_phase(state) {
-        Ok(RuntimePhase::Transcribing) => return,
-        Ok(RuntimePhase::Listening) => return,
-        Ok(RuntimePhase::Idle) => {}
-        Err(err) => {
-            emit_status(app, DictationPhase::Error, Some(err));
-            return;
-        }
+/// The longest run of whole lines at the end of `raw` that fits in `budget`
+/// bytes.
+fn newest_history_lines(raw: &str, budget: usize) -> &str {
+    if raw.len() <= budget {
+        return raw;
     }
 
-    match is_runtime_ready(state) {
-        Ok(true) => {}
-        Ok(false) => {
-            emit_status(
-                app,
-                DictationPhase::Bootstrapping,
-                Some("ASR setup still running. Please wait...".to_string()),
-            );
-            return;
-        }
-        Err(err) => {
-            emit_status(app, DictationPhase::Error, Some(err));
-            return;
-        }
-    }
+    // Starts after the first line break at or before the budget's edge, so
+    // no entry is cut in half.
+    let edge = raw.len() - budget - 1;
+    raw.as_bytes()[edge..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or("", |offset| &raw[edge + offset + 1..])
+}
 
-    let settings = match state.settings.lock() {
-        Ok(settings) => settings.clone(),
-        Err(_) => {
-            emit_status(
-                app,
-                DictationPhase::Error,
-                Some("Failed to lock settings".to_string()),
-            );
-            return;
-        }
+/// Newest entries first. Lines that fail to parse are skipped.
+fn read_history(app: &AppHandle, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+    let path = history_path(app)?;
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("Failed to read history {}: {err}", path.display())),
     };
 
-    match start_recorder(app, &settings) {
-        Ok(session) => {
-            *active = Some(session);
-            let _ = set_phase(state, RuntimePhase::Listening);
-            emit_status(
-                app,
-                DictationPhase::Listening,
-                Some("Listening...".to_string()),
-            );
+    Ok(raw
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect())
+}
+
+/// Writes a `-1.0..=1.0` sample in the target format. Power-of-two scaling
+/// keeps 16-bit samples bit-exact across a read/write round trip.
+fn write_float_sample(
+    writer: &mut WavWriter<std::io::BufWriter<std::fs::File>>,
+    format: RecordingFormat,
+    sample: f32,
+) -> hound::Result<()> {
+    let clamped = sample.clamp(-1.0, 1.0);
+    match format {
+        RecordingFormat::Pcm16 => {
+            let scaled = (clamped * 32_768.0).round() as i32;
+            writer.write_sample(scaled.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
         }
-        Err(err) => {
-            let _ = set_phase(state, RuntimePhase::Idle);
-            emit_status(app, DictationPhase::Error, Some(err));
+        RecordingFormat::Pcm24 => {
+            let scaled = (clamped * 8_388_608.0).round() as i32;
+            writer.write_sample(scaled.clamp(-8_388_608, 8_388_607))
         }
+        RecordingFormat::Float32 => writer.write_sample(clamped),
     }
 }
 
-fn worker_stop(app: &AppHandle, state: &Arc<AppRuntime>, active: &mut Option<RecorderSession>) {
-    if current_phase(state).ok() != Some(RuntimePhase::Listening) {
+fn write_i16_samples(
+    samples: &[i16],
+    format: RecordingFormat,
+    writer: &Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
+) {
+    let Ok(mut guard) = writer.lock() else {
         return;
-    }
+    };
 
-    let Some(session) = active.take() else {
+    let Some(writer) = guard.as_mut() else {
         return;
     };
 
-    let audio_path = match session.finalize() {
-        Ok(path) => path,
-        Err(err) => {
-            let _ = set_phase(state, RuntimePhase::Idle);
-            emit_status(app, DictationPhase::Error, Some(err));
-            return;
-        }
+    for &sample in samples {
+        let _ = write_float_sample(writer, format, sample as f32 / 32_768.0);
+    }
+}
+
+fn write_u16_samples(
+    samples: &[u16],
+    format: RecordingFormat,
+    writer: &Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
+) {
+    let Ok(mut guard) = writer.lock() else {
+        return;
     };
 
-    let _ = set_phase(state, RuntimePhase::Transcribing);
-    emit_status(
-        app,
-        DictationPhase::Transcribing,
-        Some("Transcribing speech...".to_string()),
-    );
+    let Some(writer) = guard.as_mut() else {
+        return;
+    };
 
-    let settings = match state.settings.lock() {
-        Ok(settings) => settings.clone(),
-        Err(_) => {
-            let _ = set_phase(state, RuntimePhase::Idle);
-            emit_status(
-                app,
-                DictationPhase::Error,
-                Some("Failed to lock settings".to_string()),
-            );
-            return;
-        }
+    for &sample in samples {
+        let centered = (sample as i32 - 32_768) as i16;
+        let _ = write_float_sample(writer, format, centered as f32 / 32_768.0);
+    }
+}
+
+fn write_f32_samples(
+    samples: &[f32],
+    format: RecordingFormat,
+    writer: &Arc<Mutex<Option<WavWriter<std::io::BufWriter<std::fs::File>>>>>,
+) {
+    let Ok(mut guard) = writer.lock() else {
+        return;
     };
 
-    let transcript = transcribe_audio(&settings, app, &audio_path);
+    let Some(writer) = guard.as_mut() else {
+        return;
+    };
 
-    match transcript {
-        Ok(text) => {
-            let _ = app.emit(TRANSCRIPT_EVENT, text.clone());
+    for &sample in samples {
+        let _ = write_float_sample(writer, format, sample);
+    }
+}
 
-            if let Some(overlay) = app.get_webview_window(OVERLAY_LABEL) {
-                let _ = overlay.hide();
-            }
+fn resolve_input_device(settings: &AppSettings) -> Result<cpal::Device, AppError> {
+    let host = cpal::default_host();
 
-            if let Err(err) = inject_text_at_cursor(&text) {
-                emit_status(app, DictationPhase::Error, Some(err));
-            }
+    let mut devices: Vec<cpal::Device> = host
+        .input_devices()
+        .map_err(|err| {
+            AppError::new(
+                ErrorCode::DeviceNotFound,
+                format!("Failed to list input devices: {err}"),
+            )
+        })?
+        .collect();
+
+    let preferred = std::iter::once(&settings.input_device).chain(&settings.fallback_devices);
+    for wanted in preferred {
+        if wanted == DEFAULT_INPUT_DEVICE {
+            break;
         }
-        Err(err) => {
-            emit_status(app, DictationPhase::Error, Some(err));
+
+        if let Some(index) = devices
+            .iter()
+            .position(|device| device.name().is_ok_and(|name| name == *wanted))
+        {
+            return Ok(devices.swap_remove(index));
         }
     }
 
-    let _ = fs::remove_file(&audio_path);
-    let _ = set_phase(state, RuntimePhase::Idle);
-    emit_status(app, DictationPhase::Idle, None);
+    host.default_input_device().ok_or_else(|| {
+        let message = if settings.input_device == DEFAULT_INPUT_DEVICE {
+            "No default microphone found".to_string()
+        } else {
+            format!(
+                "Configured microphone '{}' not found and no default device available",
+                settings.input_device
+            )
+        };
+        AppError::new(ErrorCode::DeviceNotFound, message)
+    })
 }
 
-fn run_worker_loop(app: AppHandle, state: Arc<AppRuntime>, rx: Receiver<WorkerCommand>) {
-    let mut active_session: Option<RecorderSession> = None;
+/// Falls back to the default output when the configured one is gone, so a
+/// disconnected headset never silences cues for good.
+fn resolve_output_device(name: &str) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
 
-    while let Ok(command) = rx.recv() {
-        match command {
-            WorkerCommand::Start => worker_start(&app, &state, &mut active_session),
-            WorkerCommand::Stop => worker_stop(&app, &state, &mut active_session),
-            WorkerCommand::Toggle => {
-                if current_phase(&state).ok() == Some(RuntimePhase::Listening) {
-                    worker_stop(&app, &state, &mut active_session);
-                } else {
-                    worker_start(&app, &state, &mut active_session);
-                }
-            }
+    if name != DEFAULT_OUTPUT_DEVICE {
+        let found = host
+            .output_devices()
+            .map_err(|err| format!("Failed to list output devices: {err}"))?
+            .find(|device| device.name().is_ok_and(|device_name| device_name == name));
+        match found {
+            Some(device) => return Ok(device),
+            None => eprintln!("output device '{name}' not found, using the default"),
         }
     }
+
+    host.default_output_device()
+        .ok_or_else(|| "No default output device found".to_string())
 }
 
-fn queue_command(state: &Arc<AppRuntime>, command: WorkerCommand) -> Result<(), String> {
-    if current_phase(state).ok() == Some(RuntimePhase::Transcribing) {
-        match command {
-            WorkerCommand::Start | WorkerCommand::Stop | WorkerCommand::Toggle => {
-                return Ok(());
-            }
+fn is_capturable_format(format: SampleFormat) -> bool {
+    matches!(
+        format,
+        SampleFormat::I16 | SampleFormat::U16 | SampleFormat::F32
+    )
+}
+
+/// A capture config at exactly `sample_rate`, preferring mono, or `None`
+/// when no supported range includes it.
+fn pinned_input_config(device: &cpal::Device, sample_rate: u32) -> Option<SupportedStreamConfig> {
+    let ranges: Vec<_> = match device.supported_input_configs() {
+        Ok(ranges) => ranges
+            .filter(|range| {
+                is_capturable_format(range.sample_format())
+                    && range.min_sample_rate().0 <= sample_rate
+                    && range.max_sample_rate().0 >= sample_rate
+            })
+            .collect(),
+        Err(err) => {
+            eprintln!("cannot pin capture rate, failed to read input configs: {err}");
+            return None;
         }
+    };
+
+    let range = ranges
+        .iter()
+        .find(|range| range.channels() == 1)
+        .or_else(|| ranges.first());
+    if range.is_none() {
+        eprintln!("input device does not support {sample_rate} Hz, using its default rate");
     }
 
-    state
-        .worker_tx
-        .send(command)
-        .map_err(|err| format!("Failed to send worker command: {err}"))
+    range.map(|range| (*range).with_sample_rate(SampleRate(sample_rate)))
 }
 
-fn start_dictation_internal(state: &Arc<AppRuntime>) -> Result<(), String> {
-    queue_command(state, WorkerCommand::Start)
+fn resolve_input_config(device: &cpal::Device) -> Result<SupportedStreamConfig, String> {
+    let default_err = match device.default_input_config() {
+        Ok(config) => return Ok(config),
+        Err(err) => err,
+    };
+
+    eprintln!("default input config unavailable ({default_err}), probing supported configs");
+
+    let ranges: Vec<_> = device
+        .supported_input_configs()
+        .map_err(|err| format!("Failed to read input config: {default_err}; {err}"))?
+        .filter(|range| is_capturable_format(range.sample_format()))
+        .collect();
+
+    let preferred = ranges.iter().find(|range| {
+        range.channels() == 1
+            && range.min_sample_rate().0 <= PREFERRED_SAMPLE_RATE
+            && range.max_sample_rate().0 >= PREFERRED_SAMPLE_RATE
+    });
+
+    let config = match preferred {
+        Some(range) => (*range).with_sample_rate(SampleRate(PREFERRED_SAMPLE_RATE)),
+        None => ranges
+            .first()
+            .cloned()
+            .map(|range| range.with_max_sample_rate())
+            .ok_or_else(|| {
+                format!("Failed to read input config: {default_err}; no supported fallback found")
+            })?,
+    };
+
+    eprintln!(
+        "using fallback input config: {} channel(s), {} Hz, {:?}",
+        config.channels(),
+        config.sample_rate().0,
+        config.sample_format()
+    );
+
+    Ok(config)
 }
 
-fn stop_dictation_internal(state: &Arc<AppRuntime>) -> Result<(), String> {
-    queue_command(state, WorkerCommand::Stop)
+/// WASAPI captures an output device in loopback mode when an input stream
+/// is opened on it.
+#[cfg(windows)]
+fn resolve_loopback_device() -> Result<(cpal::Device, SupportedStreamConfig), AppError> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::DeviceNotFound,
+                "No default output device to capture system audio from",
+            )
+        })?;
+    let config = device
+        .default_output_config()
+        .map_err(|err| format!("Failed to read output config for loopback: {err}"))?;
+
+    Ok((device, config))
 }
 
-fn toggle_dictation_internal(state: &Arc<AppRuntime>) -> Result<(), String> {
-    queue_command(state, WorkerCommand::Toggle)
+/// PulseAudio and PipeWire expose what is playing as "Monitor of ..." input
+/// devices; other hosts have no loopback source.
+#[cfg(not(windows))]
+fn resolve_loopback_device() -> Result<(cpal::Device, SupportedStreamConfig), AppError> {
+    let device = cpal::default_host()
+        .input_devices()
+        .map_err(|err| {
+            AppError::new(
+                ErrorCode::DeviceNotFound,
+                format!("Failed to list input devices: {err}"),
+            )
+        })?
+        .find(|device| {
+            device
+                .name()
+                .is_ok_and(|name| name.to_ascii_lowercase().contains("monitor"))
+        })
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::DeviceNotFound,
+                "No monitor source found. System audio capture needs WASAPI or a PulseAudio/PipeWire monitor device",
+            )
+        })?;
+    let config = resolve_input_config(&device)?;
+
+    Ok((device, config))
 }
 
-fn normalize_shortcut_key_token(token: &str) -> Result<String, String> {
-    let trimmed = token.trim();
-    if trimmed.is_empty() {
-        return Err("Shortcut key cannot be empty".to_string());
-    }
+fn start_recorder(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    settings: &AppSettings,
+) -> Result<RecorderSession, AppError> {
+    let (input_device, supported) = match settings.capture_source {
+        CaptureSource::Microphone => {
+            let device = resolve_input_device(settings)?;
+            let pinned = settings
+                .capture_sample_rate
+                .and_then(|sample_rate| pinned_input_config(&device, sample_rate));
+            let config = match pinned {
+                Some(config) => config,
+                None => resolve_input_config(&device)?,
+            };
+            (device, config)
+        }
+        CaptureSource::SystemLoopback => resolve_loopback_device()?,
+    };
+    let device_name = input_device.name().unwrap_or_default();
+    let _ = app.emit(
+        INPUT_DEVICE_EVENT,
+        SelectedInputDevice {
+            fell_back: settings.capture_source == CaptureSource::Microphone
+                && settings.input_device != DEFAULT_INPUT_DEVICE
+                && device_name != settings.input_device,
+            name: device_name,
+        },
+    );
 
-    if trimmed.eq_ignore_ascii_case("space") || trimmed == " " {
-        return Ok("Space".to_string());
-    }
+    let wav_path = next_wav_path(app, state)?;
+    let format = settings.recording_format;
+    let spec = format.wav_spec(supported.channels(), supported.sample_rate().0);
 
-    if trimmed.eq_ignore_ascii_case("esc") || trimmed.eq_ignore_ascii_case("escape") {
-        return Ok("Escape".to_string());
-    }
+    let writer = WavWriter::create(&wav_path, spec).map_err(|err| {
+        format!(
+            "Failed to create recording at {}: {err}. Check free disk space and folder permissions.",
+            wav_path.display()
+        )
+    })?;
+    let writer = Arc::new(Mutex::new(Some(writer)));
 
-    if trimmed.eq_ignore_ascii_case("enter") {
-        return Ok("Enter".to_string());
-    }
+    let stream_config: StreamConfig = supported.clone().into();
+    let err_fn = || {
+        let worker_tx = state.worker_tx.clone();
+        let path = wav_path.clone();
+        move |err: StreamError| {
+            eprintln!("audio input stream error: {err}");
+            let _ = worker_tx.send(WorkerCommand::StreamFailed {
+                path: path.clone(),
+                message: err.to_string(),
+            });
+        }
+    };
 
-    if trimmed.eq_ignore_ascii_case("tab") {
-        return Ok("Tab".to_string());
-    }
+    let stream = match supported.sample_format() {
+        SampleFormat::I16 => {
+            let writer = writer.clone();
+            input_device
+                .build_input_stream(
+                    &stream_config,
+                    move |data: &[i16], _| write_i16_samples(data, format, &writer),
+                    err_fn(),
+                    None,
+                )
+                .map_err(|err| format!("Failed to build i16 input stream: {err}"))?
+        }
+        SampleFormat::U16 => {
+            let writer = writer.clone();
+            input_device
+                .build_input_stream(
+                    &stream_config,
+                    move |data: &[u16], _| write_u16_samples(data, format, &writer),
+                    err_fn(),
+                    None,
+                )
+                .map_err(|err| format!("Failed to build u16 input stream: {err}"))?
+        }
+        SampleFormat::F32 => {
+            let writer = writer.clone();
+            input_device
+                .build_input_stream(
+                    &stream_config,
+                    move |data: &[f32], _| write_f32_samples(data, format, &writer),
+                    err_fn(),
+                    None,
+                )
+                .map_err(|err| format!("Failed to build f32 input stream: {err}"))?
+        }
+        other => {
+            return Err(format!("Unsupported sample format: {other:?}").into());
+        }
+    };
 
-    if trimmed.eq_ignore_ascii_case("backspace") {
-        return Ok("Backspace".to_string());
-    }
+    stream
+        .play()
+        .map_err(|err| format!("Failed to start audio capture: {err}"))?;
 
-    if trimmed.eq_ignore_ascii_case("delete") {
-        return Ok("Delete".to_string());
-    }
+    Ok(RecorderSession {
+        stream,
+        writer,
+        path: wav_path,
+        started_at: Instant::now(),
+        target: settings
+            .focus_guard
+            .then(FocusTarget::capture)
+            .unwrap_or_default(),
+    })
+}
 
-    if trimmed.eq_ignore_ascii_case("up") || trimmed.eq_ignore_ascii_case("arrowup") {
-        return Ok("ArrowUp".to_string());
-    }
+/// Whether a capture failure looks like another app briefly holding the
+/// device, e.g. WASAPI exclusive mode, rather than a real misconfiguration.
+fn is_device_busy_error(err: &str) -> bool {
+    let lower = err.to_ascii_lowercase();
+    ["in use", "busy", "exclusive", "0x8889000a", "not available"]
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
 
-    if trimmed.eq_ignore_ascii_case("down") || trimmed.eq_ignore_ascii_case("arrowdown") {
-        return Ok("ArrowDown".to_string());
+/// `start_recorder`, retried up to `recorder_retries` times while the
+/// device reports busy.
+fn start_recorder_with_retries(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    settings: &AppSettings,
+) -> Result<RecorderSession, String> {
+    let mut attempt = 0;
+    loop {
+        match start_recorder(app, state, settings).map_err(String::from) {
+            Err(err) if attempt < settings.recorder_retries && is_device_busy_error(&err) => {
+                attempt += 1;
+                eprintln!(
+                    "capture device busy, retrying ({attempt}/{}): {err}",
+                    settings.recorder_retries
+                );
+                thread::sleep(RECORDER_RETRY_DELAY);
+            }
+            result => return result,
+        }
     }
+}
 
-    if trimmed.eq_ignore_ascii_case("left") || trimmed.eq_ignore_ascii_case("arrowleft") {
-        return Ok("ArrowLeft".to_string());
+/// Reads any recording format as interleaved samples in `-1.0..=1.0`.
+fn read_normalized_samples(path: &Path) -> Result<(WavSpec, Vec<f32>), String> {
+    let mut reader =
+        WavReader::open(path).map_err(|err| format!("Failed to read recording: {err}"))?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        WavSampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>(),
+        WavSampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample.max(1) - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|value| value as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()
+        }
     }
+    .map_err(|err| format!("Failed to read recording: {err}"))?;
 
-    if trimmed.eq_ignore_ascii_case("right") || trimmed.eq_ignore_ascii_case("arrowright") {
-        return Ok("ArrowRight".to_string());
+    Ok((spec, samples))
+}
+
+/// Overall RMS of a finalized recording, normalized to `0.0..=1.0`.
+fn recording_rms(path: &Path) -> Result<f32, String> {
+    let (_, samples) = read_normalized_samples(path)?;
+    if samples.is_empty() {
+        return Ok(0.0);
     }
 
-    if trimmed.len() == 1 {
-        let ch = trimmed.chars().next().unwrap_or_default();
-        if ch.is_ascii_alphabetic() {
-            return Ok(ch.to_ascii_uppercase().to_string());
-        }
+    let sum_squares: f64 = samples
+        .iter()
+        .map(|&sample| sample as f64 * sample as f64)
+        .sum();
+    Ok((sum_squares / samples.len() as f64).sqrt() as f32)
+}
 
-        if ch.is_ascii_digit() {
-            return Ok(ch.to_string());
-        }
+/// Trims frames quieter than `threshold` (peak, `0.0..=1.0`) from both ends
+/// of a recording, keeping `margin_ms` of audio around the speech. Leaves
+/// the file untouched when nothing rises above the threshold.
+fn trim_recording_silence(path: &Path, threshold: f32, margin_ms: u64) -> Result<(), String> {
+    let (spec, samples) = read_normalized_samples(path)?;
+    let channels = spec.channels.max(1) as usize;
+
+    let limit = threshold.clamp(0.0, 1.0);
+    let is_loud = |frame: &[f32]| frame.iter().any(|sample| sample.abs() > limit);
+
+    let frames: Vec<&[f32]> = samples.chunks(channels).collect();
+    let Some(first) = frames.iter().position(|frame| is_loud(frame)) else {
+        return Ok(());
+    };
+    let last = frames
+        .iter()
+        .rposition(|frame| is_loud(frame))
+        .unwrap_or(first);
+
+    let margin = (spec.sample_rate as u64 * margin_ms / 1000) as usize;
+    let start = first.saturating_sub(margin);
+    let end = (last + 1 + margin).min(frames.len());
+
+    if start == 0 && end == frames.len() {
+        return Ok(());
     }
 
-    let upper = trimmed.to_ascii_uppercase();
-    if upper.starts_with('F')
-        && upper.len() <= 3
-        && upper
-            .chars()
-            .skip(1)
-            .all(|character| character.is_ascii_digit())
-    {
-        return Ok(upper);
+    let format = RecordingFormat::from_spec(spec);
+    let trimmed_path = path.with_extension("trimmed.wav");
+    let mut writer = WavWriter::create(&trimmed_path, spec)
+        .map_err(|err| format!("Failed to create trimmed recording: {err}"))?;
+    for frame in &frames[start..end] {
+        for &sample in *frame {
+            write_float_sample(&mut writer, format, sample)
+                .map_err(|err| format!("Failed to write trimmed recording: {err}"))?;
+        }
     }
+    writer
+        .finalize()
+        .map_err(|err| format!("Failed to finalize trimmed recording: {err}"))?;
 
-    Ok(trimmed.to_string())
+    fs::rename(&trimmed_path, path).map_err(|err| {
+        let _ = fs::remove_file(&trimmed_path);
+        format!("Failed to replace recording with trimmed audio: {err}")
+    })
 }
 
-fn normalize_shortcut_text(shortcut_text: &str) -> Result<String, String> {
-    let parsed_direct: Result<Shortcut, _> = shortcut_text.trim().parse();
-    if let Ok(shortcut) = parsed_direct {
-        return Ok(shortcut.into_string());
+#[derive(Debug, Clone, Copy)]
+enum AudioCue {
+    Start,
+    Stop,
+}
+
+impl AudioCue {
+    fn frequency_hz(self) -> f32 {
+        match self {
+            Self::Start => 880.0,
+            Self::Stop => 587.33,
+        }
     }
+}
 
-    let mut tokens: Vec<String> = shortcut_text
-        .split('+')
+struct ToneGenerator {
+    frequency_hz: f32,
+    sample_rate: f32,
+    total_frames: usize,
+    fade_frames: usize,
+    frame: usize,
+}
+
+impl ToneGenerator {
+    fn new(frequency_hz: f32, sample_rate: u32) -> Self {
+        let sample_rate = sample_rate as f32;
+        Self {
+            frequency_hz,
+            sample_rate,
+            total_frames: (sample_rate * CUE_DURATION.as_secs_f32()) as usize,
+            fade_frames: ((sample_rate * CUE_FADE.as_secs_f32()) as usize).max(1),
+            frame: 0,
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        if self.frame >= self.total_frames {
+            return 0.0;
+        }
+
+        // Short linear fades keep the tone from clicking at either end.
+        let remaining = self.total_frames - self.frame;
+        let envelope = (self.frame.min(remaining) as f32 / self.fade_frames as f32).min(1.0);
+        let phase =
+            std::f32::consts::TAU * self.frequency_hz * self.frame as f32 / self.sample_rate;
+        self.frame += 1;
+
+        phase.sin() * CUE_VOLUME * envelope
+    }
+}
+
+fn build_cue_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mut tone: ToneGenerator,
+) -> Result<Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                for frame in data.chunks_mut(channels) {
+                    let value: T = Sample::from_sample(tone.next_sample());
+                    frame.fill(value);
+                }
+            },
+            |err| eprintln!("audio cue stream error: {err}"),
+            None,
+        )
+        .map_err(|err| format!("Failed to build audio cue stream: {err}"))
+}
+
+fn play_tone(output_device: &str, frequency_hz: f32) -> Result<(), String> {
+    let device = resolve_output_device(output_device)?;
+    let supported = device
+        .default_output_config()
+        .map_err(|err| format!("Failed to read output config: {err}"))?;
+
+    let config: StreamConfig = supported.clone().into();
+    let tone = ToneGenerator::new(frequency_hz, config.sample_rate.0);
+
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_cue_stream::<f32>(&device, &config, tone)?,
+        SampleFormat::I16 => build_cue_stream::<i16>(&device, &config, tone)?,
+        SampleFormat::U16 => build_cue_stream::<u16>(&device, &config, tone)?,
+        other => return Err(format!("Unsupported output sample format: {other:?}")),
+    };
+
+    stream
+        .play()
+        .map_err(|err| format!("Failed to play audio cue: {err}"))?;
+    thread::sleep(CUE_DURATION + CUE_FADE);
+
+    Ok(())
+}
+
+/// Plays the cue on its own thread so it never delays the capture stream.
+fn play_audio_cue(settings: &AppSettings, cue: AudioCue) {
+    if !settings.audio_cues {
+        return;
+    }
+
+    let output_device = settings.output_device.clone();
+    thread::spawn(move || {
+        if let Err(err) = play_tone(&output_device, cue.frequency_hz()) {
+            eprintln!("audio cue failed: {err}");
+        }
+    });
+}
+
+fn resolve_transcriber_script(app: &AppHandle) -> Result<PathBuf, String> {
+    let mut candidates = Vec::new();
+
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        candidates.push(resource_dir.join("python").join("qwen_asr_transcribe.py"));
+    }
+
+    candidates.push(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("python")
+            .join("qwen_asr_transcribe.py"),
+    );
+
+    if let Ok(current_dir) = std::env::current_dir() {
+        candidates.push(
+            current_dir
+                .join("src-tauri")
+                .join("python")
+                .join("qwen_asr_transcribe.py"),
+        );
+    }
+
+    candidates
+        .into_iter()
+        .find(|path| path.exists())
+        .ok_or_else(|| "Could not locate qwen_asr_transcribe.py".to_string())
+}
+
+/// Short, actionable text for failures whose raw stderr is a wall of
+/// library output.
+fn known_failure_hint(detail: &str) -> Option<&'static str> {
+    let lower = detail.to_ascii_lowercase();
+
+    if lower.contains("out of memory") || lower.contains("outofmemoryerror") {
+        return Some("GPU out of memory. Try the 0.6B model or CPU mode.");
+    }
+
+    if lower.contains("gatedrepoerror")
+        || lower.contains("401 client error")
+        || lower.contains("invalid user token")
+        || lower.contains("huggingface-cli login")
+    {
+        return Some(
+            "Model download was not authorized. Log in with `huggingface-cli login` and check access to the model.",
+        );
+    }
+
+    if lower.contains("ffmpeg") && (lower.contains("not found") || lower.contains("no such file")) {
+        return Some("ffmpeg is missing. Install it and make sure it is on your PATH.");
+    }
+
+    None
+}
+
+fn command_error(prefix: &str, stderr: &[u8]) -> String {
+    let detail = String::from_utf8_lossy(stderr).trim().to_string();
+    if detail.is_empty() {
+        return prefix.to_string();
+    }
+
+    match known_failure_hint(&detail) {
+        Some(hint) => {
+            eprintln!("{prefix}: {detail}");
+            format!("{prefix}: {hint}")
+        }
+        None => format!("{prefix}: {detail}"),
+    }
+}
+
+fn configure_child_process(command: &mut Command) {
+    #[cfg(windows)]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+}
+
+fn venv_python(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+/// Bundled interpreter found in the resource dir at startup, if any.
+static BUNDLED_PYTHON: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+fn resolve_bundled_python(app: &AppHandle) -> Option<PathBuf> {
+    let root = app.path().resource_dir().ok()?.join(BUNDLED_PYTHON_DIR);
+    let python = if cfg!(windows) {
+        root.join("python.exe")
+    } else {
+        root.join("bin").join("python3")
+    };
+
+    python.is_file().then_some(python)
+}
+
+fn bundled_python() -> Option<PathBuf> {
+    BUNDLED_PYTHON.get().cloned().flatten()
+}
+
+/// `python_command`, or the bundled interpreter when that is selected. A
+/// missing bundle falls back to `python_command`; `ensure_python_binary`
+/// reports it before anything runs.
+fn base_python(settings: &AppSettings) -> PathBuf {
+    settings
+        .use_bundled_python
+        .then(bundled_python)
+        .flatten()
+        .unwrap_or_else(|| PathBuf::from(&settings.python_command))
+}
+
+/// Interpreter that runs pip and the sidecar: the virtualenv's when one is
+/// configured, otherwise `base_python`.
+fn python_interpreter(settings: &AppSettings) -> PathBuf {
+    let venv = settings.venv_path.trim();
+    if venv.is_empty() {
+        base_python(settings)
+    } else {
+        venv_python(Path::new(venv))
+    }
+}
+
+/// Creates the configured virtualenv with `python_command` if it is missing.
+fn ensure_virtualenv(settings: &AppSettings) -> Result<(), String> {
+    let venv = settings.venv_path.trim();
+    if venv.is_empty() || venv_python(Path::new(venv)).is_file() {
+        return Ok(());
+    }
+
+    let mut command = Command::new(base_python(settings));
+    command.args(["-m", "venv"]).arg(venv);
+    configure_child_process(&mut command);
+
+    let output = command
+        .output()
+        .map_err(|err| format!("Failed launching virtualenv creation: {err}"))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(command_error(
+            &format!("Failed to create virtualenv at '{venv}'"),
+            &output.stderr,
+        ))
+    }
+}
+
+fn sidecar_command(settings: &AppSettings, app: &AppHandle) -> Result<Command, String> {
+    let script_path = resolve_transcriber_script(app)?;
+
+    let mut command = Command::new(python_interpreter(settings));
+    command.arg(script_path);
+    // Only the hub cache moves; `HF_HOME` would also hide the login token.
+    if let Some(dir) = &settings.model_cache_dir {
+        command.env("HF_HUB_CACHE", dir);
+    }
+    configure_child_process(&mut command);
+    Ok(command)
+}
+
+/// Cleans up a pasted interpreter path: surrounding whitespace and quotes
+/// go, and on Windows a folder or extensionless path resolves to the
+/// `python.exe` it means.
+fn normalize_python_command(raw: &str) -> String {
+    let trimmed = raw.trim();
+    let unquoted = ['"', '\'']
+        .iter()
+        .find_map(|&quote| {
+            trimmed
+                .strip_prefix(quote)
+                .and_then(|rest| rest.strip_suffix(quote))
+        })
+        .unwrap_or(trimmed)
+        .trim();
+
+    if unquoted.is_empty() {
+        return AppSettings::default().python_command;
+    }
+
+    #[cfg(windows)]
+    {
+        let path = Path::new(unquoted);
+        if path.is_dir() {
+            return path.join("python.exe").to_string_lossy().into_owned();
+        }
+        if path.extension().is_none() && path.with_extension("exe").is_file() {
+            return path.with_extension("exe").to_string_lossy().into_owned();
+        }
+    }
+
+    unquoted.to_string()
+}
+
+fn ensure_python_binary(settings: &AppSettings) -> Result<String, AppError> {
+    if settings.use_bundled_python && bundled_python().is_none() {
+        return Err(AppError::new(
+            ErrorCode::PythonMissing,
+            "Bundled Python is not part of this build. Turn it off in Runtime settings to use your own Python",
+        ));
+    }
+
+    let python = base_python(settings);
+    let mut command = Command::new(&python);
+    command.arg("--version");
+    configure_child_process(&mut command);
+
+    let output = command.output().map_err(|err| {
+        AppError::new(
+            ErrorCode::PythonMissing,
+            format!(
+                "Python command '{}' failed to start: {err}",
+                python.display()
+            ),
+        )
+    })?;
+
+    if output.status.success() {
+        // Python 2 printed its version on stderr, so accept either stream.
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Ok(if stdout.is_empty() { stderr } else { stdout })
+    } else {
+        Err(AppError::new(
+            ErrorCode::PythonMissing,
+            command_error(
+                &format!("Python command '{}' is not usable", python.display()),
+                &output.stderr,
+            ),
+        ))
+    }
+}
+
+#[cfg(windows)]
+const FFMPEG_INSTALL_HINT: &str = "winget install ffmpeg";
+
+#[cfg(target_os = "macos")]
+const FFMPEG_INSTALL_HINT: &str = "brew install ffmpeg";
+
+#[cfg(not(any(windows, target_os = "macos")))]
+const FFMPEG_INSTALL_HINT: &str = "sudo apt install ffmpeg (or your distro's package manager)";
+
+/// The ASR stack shells out to ffmpeg to decode compressed audio, and its own
+/// error for a missing binary is buried in a traceback. Recordings are WAV,
+/// so dictation works without it.
+fn check_ffmpeg() -> Result<String, String> {
+    let mut command = Command::new("ffmpeg");
+    command.arg("-version");
+    configure_child_process(&mut command);
+
+    let missing = || {
+        format!("ffmpeg is missing, so only WAV files can be transcribed. Install it with {FFMPEG_INSTALL_HINT} and make sure it is on your PATH")
+    };
+    let output = command.output().map_err(|_| missing())?;
+    if !output.status.success() {
+        return Err(missing());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or("ffmpeg")
+        .trim()
+        .to_string())
+}
+
+fn check_python_dependencies(settings: &AppSettings) -> Result<(), AppError> {
+    let python = python_interpreter(settings);
+    let modules = PYTHON_DEPENDENCIES.map(|(module, _)| module).join(", ");
+    let mut check_command = Command::new(&python);
+    check_command.arg("-c").arg(format!("import {modules}"));
+    configure_child_process(&mut check_command);
+
+    let check = check_command.output().map_err(|err| {
+        AppError::new(
+            ErrorCode::PythonMissing,
+            format!("Dependency check failed for '{}': {err}", python.display()),
+        )
+    })?;
+
+    if check.status.success() {
+        Ok(())
+    } else {
+        Err(AppError::new(
+            ErrorCode::DependencyMissing,
+            command_error("Python dependencies missing", &check.stderr),
+        ))
+    }
+}
+
+/// Pip packages whose modules are not installed at all. A module that is
+/// installed but fails to import is left alone, so a transient import error
+/// never triggers a reinstall.
+fn missing_python_packages(settings: &AppSettings) -> Result<Vec<&'static str>, String> {
+    let python = python_interpreter(settings);
+    let mut command = Command::new(&python);
+    command
+        .arg("-c")
+        .arg("import importlib.util, sys; print(' '.join(m for m in sys.argv[1:] if importlib.util.find_spec(m) is None))")
+        .args(PYTHON_DEPENDENCIES.iter().map(|(module, _)| module));
+    configure_child_process(&mut command);
+
+    let output = command
+        .output()
+        .map_err(|err| format!("Dependency check failed for '{}': {err}", python.display()))?;
+    if !output.status.success() {
+        return Err(command_error("Dependency check failed", &output.stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let missing: Vec<&str> = stdout.split_whitespace().collect();
+    Ok(PYTHON_DEPENDENCIES
+        .iter()
+        .filter(|(module, _)| missing.contains(module))
+        .map(|(_, package)| *package)
+        .collect())
+}
+
+/// The pinned specifier for `package`, or the bare name.
+fn pinned_requirement(package: &str, pins: &[String]) -> String {
+    pins.iter()
+        .find(|pin| {
+            let name = pin
+                .split(|ch: char| "=<>!~[; ".contains(ch))
+                .next()
+                .unwrap_or_default();
+            name.eq_ignore_ascii_case(package)
+        })
+        .cloned()
+        .unwrap_or_else(|| package.to_string())
+}
+
+/// Runs pip, passing each line of its stdout to `on_progress` as it
+/// arrives. Returns whether pip succeeded along with its stderr.
+fn run_pip_install(
+    python: &Path,
+    user_install: bool,
+    requirements: &[String],
+    on_progress: &mut dyn FnMut(&str),
+) -> Result<(bool, String), String> {
+    let mut install_command = Command::new(python);
+    install_command.args(["-m", "pip", "install", "--progress-bar", "off"]);
+    if user_install {
+        install_command.arg("--user");
+    }
+    install_command
+        .args(requirements)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    configure_child_process(&mut install_command);
+
+    let mut child = install_command
+        .spawn()
+        .map_err(|err| format!("Failed launching pip installer: {err}"))?;
+
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = stderr.read_to_end(&mut buffer);
+            buffer
+        })
+    });
+
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let line = line.trim();
+            if !line.is_empty() {
+                on_progress(line);
+            }
+        }
+    }
+
+    let status = child
+        .wait()
+        .map_err(|err| format!("Failed to wait for pip installer: {err}"))?;
+    let stderr = stderr_reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+
+    Ok((
+        status.success(),
+        String::from_utf8_lossy(&stderr).trim().to_string(),
+    ))
+}
+
+/// Installs only `packages`, never upgrading what is already there.
+fn install_python_dependencies(
+    settings: &AppSettings,
+    packages: &[&str],
+    on_progress: &mut dyn FnMut(&str),
+) -> Result<(), String> {
+    let requirements: Vec<String> = packages
+        .iter()
+        .map(|package| pinned_requirement(package, &settings.dependency_pins))
+        .collect();
+
+    // Outside our own virtualenv or bundle, keep pip away from the global
+    // site-packages.
+    let python = python_interpreter(settings);
+    let user_install = settings.venv_path.trim().is_empty() && !settings.use_bundled_python;
+    let (mut succeeded, mut stderr) =
+        run_pip_install(&python, user_install, &requirements, on_progress)?;
+
+    // `python_command` may itself point into a virtualenv, where pip
+    // refuses `--user`.
+    if user_install && !succeeded && stderr.contains("User site-packages are not visible") {
+        (succeeded, stderr) = run_pip_install(&python, false, &requirements, on_progress)?;
+    }
+
+    if succeeded {
+        return Ok(());
+    }
+
+    if stderr.contains("externally-managed-environment") {
+        return Err(format!(
+            "Python at '{}' is externally managed (PEP 668), so pip cannot install into it. Set a virtualenv path in Runtime settings and dependencies will be installed there.",
+            python.display()
+        ));
+    }
+
+    Err(command_error(
+        &format!(
+            "Auto-install failed (pip install {})",
+            requirements.join(" ")
+        ),
+        stderr.as_bytes(),
+    ))
+}
+
+fn ensure_python_dependencies(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    settings: &AppSettings,
+) -> Result<(), AppError> {
+    let import_error = match check_python_dependencies(settings) {
+        Ok(()) => return Ok(()),
+        Err(err) if err.code == ErrorCode::DependencyMissing => err.message,
+        Err(err) => return Err(err),
+    };
+
+    let missing = missing_python_packages(settings)?;
+    if missing.is_empty() {
+        // Everything is installed, so reinstalling would only churn a
+        // working environment.
+        return Err(AppError::new(
+            ErrorCode::DependencyMissing,
+            format!(
+                "{import_error}. The packages are installed but failed to import; repair them manually"
+            ),
+        ));
+    }
+
+    if !settings.auto_install_deps {
+        return Err(AppError::new(
+            ErrorCode::DependencyMissing,
+            format!(
+                "{import_error}. Auto-install is disabled; install them with: pip install {}",
+                missing.join(" ")
+            ),
+        ));
+    }
+
+    let emit_install = |stage: InstallStage, message: Option<String>| {
+        let _ = app.emit(INSTALL_EVENT, InstallProgress { stage, message });
+    };
+
+    emit_install(InstallStage::Started, None);
+    let installed = install_python_dependencies(settings, &missing, &mut |line| {
+        emit_bootstrap_status(
+            app,
+            state,
+            BootstrapStage::InstallingDeps,
+            Some(format!("Installing ASR dependencies: {line}")),
+        );
+    });
+
+    match &installed {
+        Ok(()) => emit_install(InstallStage::Finished, None),
+        Err(err) => emit_install(InstallStage::Failed, Some(err.clone())),
+    }
+
+    installed.map_err(|err| AppError::new(ErrorCode::DependencyMissing, err))
+}
+
+fn warmup_selected_model(settings: &AppSettings, app: &AppHandle) -> Result<(), String> {
+    let mut command = sidecar_command(settings, app)?;
+    command
+        .arg("--warmup")
+        .arg("--model")
+        .arg(settings.model.as_hf_id())
+        .arg("--language")
+        .arg(&settings.language)
+        .arg("--device")
+        .arg(settings.compute_device.as_arg());
+
+    let output = command
+        .output()
+        .map_err(|err| format!("Failed launching model warmup: {err}"))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(command_error("Model warmup failed", &output.stderr))
+    }
+}
+
+fn probe_compute(settings: &AppSettings, app: &AppHandle) -> Result<ComputeProbe, String> {
+    let mut command = sidecar_command(settings, app)?;
+    command.arg("--probe");
+
+    let output = command
+        .output()
+        .map_err(|err| format!("Failed launching compute probe: {err}"))?;
+
+    if !output.status.success() {
+        return Err(command_error("Compute probe failed", &output.stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    serde_json::from_str::<ComputeProbe>(stdout.trim())
+        .map_err(|err| format!("Invalid compute probe response: {err}"))
+}
+
+fn query_environment_internal(
+    settings: &AppSettings,
+    app: &AppHandle,
+) -> Result<EnvironmentInfo, String> {
+    let mut command = sidecar_command(settings, app)?;
+    command
+        .arg("--info")
+        .arg("--device")
+        .arg(settings.compute_device.as_arg())
+        .arg("--candidates");
+    for model in ModelOption::ALL {
+        command.arg(model.as_hf_id());
+    }
+
+    let output = command
+        .output()
+        .map_err(|err| format!("Failed launching environment query: {err}"))?;
+
+    if !output.status.success() {
+        return Err(command_error("Environment query failed", &output.stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let environment = serde_json::from_str::<SidecarEnvironment>(stdout.trim())
+        .map_err(|err| format!("Invalid environment response: {err}"))?;
+
+    let models = ModelOption::ALL
+        .into_iter()
+        .map(|model| ModelAvailability {
+            model,
+            id: model.as_hf_id().to_string(),
+            cached: environment
+                .cached_models
+                .get(model.as_hf_id())
+                .copied()
+                .unwrap_or(false),
+        })
+        .collect();
+
+    Ok(EnvironmentInfo {
+        torch_version: environment.torch_version,
+        cuda_version: environment.cuda_version,
+        cuda_device: environment.cuda_device,
+        device: environment.device,
+        models,
+    })
+}
+
+fn describe_compute(probe: &ComputeProbe) -> Result<String, String> {
+    if probe.cuda {
+        let device = probe.cuda_device.as_deref().unwrap_or("unknown device");
+        Ok(format!("CUDA available ({device})"))
+    } else if probe.mps {
+        Ok("Apple MPS available".to_string())
+    } else {
+        Err("No GPU detected, the model will run on CPU".to_string())
+    }
+}
+
+fn run_diagnostics_internal(app: &AppHandle, state: &Arc<AppRuntime>) -> Vec<DiagnosticCheck> {
+    let settings = match state.settings.lock() {
+        Ok(settings) => settings.clone(),
+        Err(_) => {
+            return vec![DiagnosticCheck::from_result(
+                "Settings",
+                Err("Failed to lock settings".to_string()),
+            )];
+        }
+    };
+
+    let mut checks = Vec::new();
+
+    let python = ensure_python_binary(&settings);
+    let python_ok = python.is_ok();
+    checks.push(DiagnosticCheck::from_result("Python runtime", python));
+
+    if python_ok {
+        checks.push(DiagnosticCheck::from_result(
+            "ASR dependencies",
+            check_python_dependencies(&settings).map(|_| {
+                let modules = PYTHON_DEPENDENCIES.map(|(module, _)| module).join(", ");
+                format!("{modules} import cleanly")
+            }),
+        ));
+        checks.push(DiagnosticCheck::from_result("ffmpeg", check_ffmpeg()));
+        checks.push(DiagnosticCheck::from_result(
+            "GPU acceleration",
+            probe_compute(&settings, app).and_then(|probe| describe_compute(&probe)),
+        ));
+    }
+
+    checks.push(DiagnosticCheck::from_result(
+        "Input device",
+        resolve_input_device(&settings)
+            .map_err(String::from)
+            .and_then(|device| device.name().map_err(|err| err.to_string())),
+    ));
+
+    let shortcut = state
+        .registered_shortcut
+        .lock()
+        .map(|shortcut| shortcut.clone())
+        .map_err(|_| "Failed to lock shortcut state".to_string());
+    checks.push(DiagnosticCheck::from_result(
+        "Global shortcut",
+        shortcut.and_then(|shortcut| {
+            if ModifierTrigger::parse(&shortcut).is_some() {
+                return Ok(format!("{shortcut} is watched as a modifier-only trigger"));
+            }
+
+            let parsed: Shortcut = shortcut
+                .parse()
+                .map_err(|err| format!("Invalid shortcut '{shortcut}': {err}"))?;
+            if app.global_shortcut().is_registered(parsed) {
+                Ok(format!("{shortcut} is registered"))
+            } else {
+                Err(format!("{shortcut} is not registered"))
+            }
+        }),
+    ));
+
+    checks
+}
+
+/// Bootstrap progress only reaches the UI while idle, so it can't clobber
+/// the status of a recording queued during bootstrap.
+fn emit_bootstrap_status(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    stage: BootstrapStage,
+    message: Option<String>,
+) {
+    if current_phase(state).ok() == Some(RuntimePhase::Idle) {
+        emit_status_payload(
+            app,
+            DictationStatus {
+                phase: stage.phase(),
+                message,
+                stage: Some(stage),
+                overlay_text: None,
+            },
+            Duration::ZERO,
+        );
+    }
+}
+
+/// Blocks until any running bootstrap finishes and reports whether the
+/// runtime came up.
+fn wait_for_runtime_ready(state: &Arc<AppRuntime>) -> Result<bool, String> {
+    if is_runtime_ready(state)? {
+        return Ok(true);
+    }
+
+    loop {
+        let requested = current_bootstrap_generation(state)?;
+        drop(
+            state
+                .bootstrap_lock
+                .lock()
+                .map_err(|_| "Failed to lock bootstrap state".to_string())?,
+        );
+        if is_runtime_ready(state)? {
+            return Ok(true);
+        }
+        // A superseded run gave up; keep waiting for the one that replaced it.
+        if current_bootstrap_generation(state)? == requested {
+            return Ok(false);
+        }
+    }
+}
+
+fn current_bootstrap_generation(state: &Arc<AppRuntime>) -> Result<u64, String> {
+    state
+        .bootstrap_generation
+        .lock()
+        .map(|generation| *generation)
+        .map_err(|_| "Failed to lock bootstrap state".to_string())
+}
+
+/// Fails with `BOOTSTRAP_SUPERSEDED` once a newer bootstrap was requested.
+fn ensure_current_bootstrap(state: &Arc<AppRuntime>, generation: u64) -> Result<(), String> {
+    if current_bootstrap_generation(state)? != generation {
+        return Err(BOOTSTRAP_SUPERSEDED.to_string());
+    }
+    Ok(())
+}
+
+fn bootstrap_asr_runtime(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    settings: AppSettings,
+    generation: u64,
+) -> Result<(), String> {
+    let _bootstrap_guard = state
+        .bootstrap_lock
+        .lock()
+        .map_err(|_| "Failed to lock bootstrap state".to_string())?;
+    // Runs queued behind the lock are skipped outright when a newer one is
+    // already waiting.
+    ensure_current_bootstrap(state, generation)?;
+
+    let _ = set_runtime_ready(state, false);
+    emit_bootstrap_status(
+        app,
+        state,
+        BootstrapStage::CheckingPython,
+        Some("Checking Python runtime...".to_string()),
+    );
+
+    ensure_python_binary(&settings)?;
+
+    let recordings_dir = resolve_recordings_dir(app)?;
+    if let Ok(mut dir) = state.recordings_dir.lock() {
+        *dir = Some(recordings_dir);
+    }
+
+    ensure_current_bootstrap(state, generation)?;
+    if !settings.venv_path.trim().is_empty() {
+        emit_bootstrap_status(
+            app,
+            state,
+            BootstrapStage::CheckingPython,
+            Some("Preparing virtualenv...".to_string()),
+        );
+        ensure_virtualenv(&settings)?;
+    }
+
+    emit_bootstrap_status(
+        app,
+        state,
+        BootstrapStage::InstallingDeps,
+        Some("Ensuring ASR dependencies are installed...".to_string()),
+    );
+    ensure_current_bootstrap(state, generation)?;
+    ensure_python_dependencies(app, state, &settings)?;
+
+    emit_bootstrap_status(
+        app,
+        state,
+        BootstrapStage::InstallingDeps,
+        Some("Checking for ffmpeg...".to_string()),
+    );
+    let ffmpeg_warning = check_ffmpeg().err();
+    if let Some(warning) = &ffmpeg_warning {
+        eprintln!("{warning}");
+    }
+
+    ensure_current_bootstrap(state, generation)?;
+    emit_bootstrap_status(
+        app,
+        state,
+        BootstrapStage::DownloadingModel,
+        Some("Checking model cache...".to_string()),
+    );
+    if let Some(dir) = &settings.model_cache_dir {
+        ensure_writable_dir(dir).map_err(|err| {
+            format!(
+                "Model cache folder {} is not writable ({err}). Pick another folder or check its permissions.",
+                dir.display()
+            )
+        })?;
+    }
+    // A failed query only loses the download hint, so it never blocks setup.
+    let cached = query_environment_internal(&settings, app).map(|environment| {
+        environment
+            .models
+            .iter()
+            .any(|availability| availability.model == settings.model && availability.cached)
+    });
+    let (stage, message) = match cached {
+        Ok(false) => (
+            BootstrapStage::DownloadingModel,
+            format!(
+                "Downloading model, this happens once ({})...",
+                settings.model.download_size()
+            ),
+        ),
+        Ok(true) => (
+            BootstrapStage::WarmingUp,
+            "Loading selected model...".to_string(),
+        ),
+        Err(err) => {
+            eprintln!("skipping model cache check: {err}");
+            (
+                BootstrapStage::DownloadingModel,
+                "Preparing selected model (first run may download)...".to_string(),
+            )
+        }
+    };
+    emit_bootstrap_status(app, state, stage, Some(message));
+    ensure_current_bootstrap(state, generation)?;
+    warmup_selected_model(&settings, app)?;
+
+    let _ = set_runtime_ready(state, true);
+    let ready = match ffmpeg_warning {
+        Some(warning) => format!("Ready. {warning}"),
+        None => "Ready".to_string(),
+    };
+    emit_bootstrap_status(app, state, BootstrapStage::Ready, Some(ready));
+    Ok(())
+}
+
+/// Records a few seconds from the configured input and reports its levels.
+/// The recording is deleted afterwards.
+fn test_microphone_internal(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    seconds: u64,
+) -> Result<MicrophoneTest, AppError> {
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+    // A dictation must not open the same device while the test records.
+    let _busy = BusyPhase::claim(
+        app,
+        state,
+        "Finish the current dictation before testing the microphone",
+    )?;
+
+    let session = start_recorder(app, state, &settings)?;
+    thread::sleep(Duration::from_secs(seconds.clamp(1, MAX_MIC_TEST_SECS)));
+    let path = session.finalize()?;
+    let samples = read_normalized_samples(&path);
+    let _ = fs::remove_file(&path);
+    let (_, samples) = samples?;
+
+    let peak = samples
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    let rms = if samples.is_empty() {
+        0.0
+    } else {
+        let sum_squares: f64 = samples
+            .iter()
+            .map(|&sample| sample as f64 * sample as f64)
+            .sum();
+        (sum_squares / samples.len() as f64).sqrt() as f32
+    };
+
+    Ok(MicrophoneTest {
+        peak,
+        rms,
+        silent: rms < settings.silence_threshold,
+    })
+}
+
+/// Reloads the model after the runtime is up, e.g. when it was swapped out
+/// while the machine sat idle.
+fn warmup_internal(app: &AppHandle, state: &Arc<AppRuntime>) -> Result<(), AppError> {
+    if !is_runtime_ready(state)? {
+        return Err(AppError::new(ErrorCode::NotReady, RUNTIME_NOT_READY));
+    }
+
+    let _bootstrap_guard = state
+        .bootstrap_lock
+        .lock()
+        .map_err(|_| "Failed to lock bootstrap state".to_string())?;
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+
+    emit_bootstrap_status(
+        app,
+        state,
+        BootstrapStage::WarmingUp,
+        Some("Warming up model...".to_string()),
+    );
+
+    let warmed = warmup_selected_model(&settings, app);
+    match &warmed {
+        Ok(()) => {
+            emit_bootstrap_status(app, state, BootstrapStage::Ready, Some("Ready".to_string()))
+        }
+        Err(err) => emit_bootstrap_status(app, state, BootstrapStage::Failed, Some(err.clone())),
+    }
+
+    warmed.map_err(AppError::from)
+}
+
+fn spawn_bootstrap_task(app: AppHandle, state: Arc<AppRuntime>, settings: AppSettings) {
+    let generation = match state.bootstrap_generation.lock() {
+        Ok(mut generation) => {
+            *generation += 1;
+            *generation
+        }
+        Err(_) => {
+            eprintln!("Failed to lock bootstrap state");
+            return;
+        }
+    };
+
+    thread::spawn(
+        move || match bootstrap_asr_runtime(&app, &state, settings, generation) {
+            Ok(()) => {}
+            // The newer run reports its own status.
+            Err(err) if err == BOOTSTRAP_SUPERSEDED => {}
+            Err(err) => {
+                let _ = set_runtime_ready(&state, false);
+                emit_bootstrap_status(&app, &state, BootstrapStage::Failed, Some(err));
+            }
+        },
+    );
+}
+
+fn parse_sidecar_output(stdout: &str) -> Result<Transcription, String> {
+    serde_json::from_str::<Transcription>(stdout.trim()).map_err(|err| {
+        eprintln!("unexpected sidecar stdout: {stdout}");
+        format!("Invalid response from sidecar: {err}")
+    })
+}
+
+fn transcribe_audio(
+    settings: &AppSettings,
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    audio_path: &Path,
+    timestamps: bool,
+) -> Result<Transcription, AppError> {
+    let mut command = sidecar_command(settings, app)?;
+    command
+        .arg("--audio")
+        .arg(audio_path)
+        .arg("--model")
+        .arg(settings.model.as_hf_id())
+        .arg("--language")
+        .arg(&settings.language)
+        .arg("--device")
+        .arg(settings.compute_device.as_arg())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(max_new_tokens) = settings.max_new_tokens {
+        command
+            .arg("--max-new-tokens")
+            .arg(max_new_tokens.to_string());
+    }
+    if let Some(beam_size) = settings.beam_size {
+        command.arg("--beam-size").arg(beam_size.to_string());
+    }
+    if let Some(temperature) = settings.temperature {
+        command.arg("--temperature").arg(temperature.to_string());
+    }
+    if timestamps {
+        command.arg("--timestamps");
+    }
+
+    let mut child = command.spawn().map_err(|err| {
+        format!(
+            "Failed to launch Python process '{}': {err}",
+            python_interpreter(settings).display()
+        )
+    })?;
+
+    // Drain stderr on its own thread so a chatty sidecar can't block on a
+    // full pipe while stdout is being read line by line.
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = stderr.read_to_end(&mut buffer);
+            buffer
+        })
+    });
+
+    let stdout = child.stdout.take();
+    set_transcription_stage(state, None);
+    *state
+        .transcription_child
+        .lock()
+        .map_err(|_| "Failed to lock transcription process".to_string())? = Some(child);
+
+    let mut final_output = String::new();
+    if let Some(stdout) = stdout {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Ok(marker) = serde_json::from_str::<TranscriptionStageLine>(&line) {
+                set_transcription_stage(state, Some(marker.stage));
+            } else {
+                final_output.push_str(&line);
+                final_output.push('\n');
+            }
+        }
+    }
+
+    // stdout closes once the sidecar exits; if the child is gone from the
+    // slot by then, it was killed by `abort_transcription`.
+    let child = state
+        .transcription_child
+        .lock()
+        .map_err(|_| "Failed to lock transcription process".to_string())?
+        .take();
+    let Some(mut child) = child else {
+        return Err(AppError::new(ErrorCode::Cancelled, TRANSCRIPTION_CANCELLED));
+    };
+
+    let status = child
+        .wait()
+        .map_err(|err| format!("Failed to wait for ASR sidecar: {err}"))?;
+    let stderr = stderr_reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+
+    if !status.success() {
+        return Err(command_error("ASR sidecar failed", &stderr).into());
+    }
+
+    let mut transcription = parse_sidecar_output(&final_output)?;
+    transcription.text = transcription.text.trim().to_string();
+
+    if transcription.text.is_empty() {
+        let message = if transcription.no_speech {
+            NOTHING_HEARD
+        } else {
+            EMPTY_TRANSCRIPT
+        };
+        return Err(AppError::new(ErrorCode::TranscriptEmpty, message));
+    }
+
+    Ok(transcription)
+}
+
+/// Like `transcribe_audio`, but a recording longer than `chunk_length_secs`
+/// goes through the sidecar in overlapping windows whose transcripts are
+/// stitched together.
+fn transcribe_long_audio(
+    settings: &AppSettings,
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    audio_path: &Path,
+    timestamps: bool,
+) -> Result<Transcription, AppError> {
+    let window_frames = WavReader::open(audio_path).ok().and_then(|reader| {
+        let window = settings.chunk_length_secs * reader.spec().sample_rate as u64;
+        (window > 0 && reader.duration() as u64 > window).then_some(window as usize)
+    });
+    let Some(window_frames) = window_frames else {
+        return transcribe_audio(settings, app, state, audio_path, timestamps);
+    };
+
+    let (spec, samples) = read_normalized_samples(audio_path)?;
+    let channels = spec.channels.max(1) as usize;
+    let total_frames = samples.len() / channels;
+    let overlap_frames = (settings.chunk_overlap_secs * spec.sample_rate as u64) as usize;
+    let step_frames = window_frames.saturating_sub(overlap_frames).max(1);
+
+    let mut stitched = Transcription {
+        text: String::new(),
+        language: None,
+        duration_ms: None,
+        no_speech: false,
+        segments: Vec::new(),
+    };
+    let mut model_came_up_empty = false;
+    let mut start = 0;
+    loop {
+        let end = (start + window_frames).min(total_frames);
+        // Chunks go next to the recordings, never beside a user's own file.
+        let chunk_path = next_wav_path(app, state)?;
+        write_recording_chunk(
+            &chunk_path,
+            spec,
+            &samples[start * channels..end * channels],
+        )?;
+
+        let chunk = transcribe_audio(settings, app, state, &chunk_path, timestamps);
+        let _ = fs::remove_file(&chunk_path);
+
+        match chunk {
+            Ok(chunk) => {
+                stitched.text = stitch_transcripts(&stitched.text, &chunk.text);
+                stitched.language = stitched.language.or(chunk.language);
+                // Segments inside the overlap were already covered by the
+                // previous window.
+                let offset_ms = start as u64 * 1000 / spec.sample_rate.max(1) as u64;
+                let covered_until = stitched.segments.last().map_or(0, |last| last.end_ms);
+                stitched.segments.extend(
+                    chunk
+                        .segments
+                        .into_iter()
+                        .map(|segment| TranscriptSegment {
+                            start_ms: segment.start_ms + offset_ms,
+                            end_ms: segment.end_ms + offset_ms,
+                            ..segment
+                        })
+                        .filter(|segment| segment.start_ms >= covered_until),
+                );
+                if let Some(duration_ms) = chunk.duration_ms {
+                    stitched.duration_ms = Some(stitched.duration_ms.unwrap_or(0) + duration_ms);
+                }
+            }
+            // A window of pure silence is expected in long captures.
+            Err(err) if err.message == NOTHING_HEARD => {}
+            Err(err) if err.message == EMPTY_TRANSCRIPT => model_came_up_empty = true,
+            Err(err) => return Err(err),
+        }
+
+        if end == total_frames {
+            break;
+        }
+        start += step_frames;
+    }
+
+    if stitched.text.is_empty() {
+        let message = if model_came_up_empty {
+            EMPTY_TRANSCRIPT
+        } else {
+            NOTHING_HEARD
+        };
+        return Err(AppError::new(ErrorCode::TranscriptEmpty, message));
+    }
+    Ok(stitched)
+}
+
+fn write_recording_chunk(path: &Path, spec: WavSpec, samples: &[f32]) -> Result<(), String> {
+    let format = RecordingFormat::from_spec(spec);
+    let mut writer = WavWriter::create(path, spec)
+        .map_err(|err| format!("Failed to create recording chunk: {err}"))?;
+    for &sample in samples {
+        write_float_sample(&mut writer, format, sample)
+            .map_err(|err| format!("Failed to write recording chunk: {err}"))?;
+    }
+    writer
+        .finalize()
+        .map_err(|err| format!("Failed to finalize recording chunk: {err}"))
+}
+
+/// Joins two transcripts, dropping the longest run of words that ends
+/// `previous` and also starts `next`, as overlapping windows both hear it.
+/// Words compare case-insensitively and without surrounding punctuation.
+fn stitch_transcripts(previous: &str, next: &str) -> String {
+    let normalize = |word: &str| {
+        word.trim_matches(|ch: char| !ch.is_alphanumeric())
+            .to_lowercase()
+    };
+    let previous_words: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let next_words: Vec<&str> = next.split_whitespace().collect();
+
+    let longest = MAX_STITCH_OVERLAP_WORDS
+        .min(previous_words.len())
+        .min(next_words.len());
+    let overlap = (1..=longest)
+        .rev()
+        .find(|&count| {
+            previous_words[previous_words.len() - count..]
+                .iter()
+                .zip(&next_words[..count])
+                .all(|(left, right)| *left == normalize(right))
+        })
+        .unwrap_or(0);
+
+    let rest = next_words[overlap..].join(" ");
+    match (previous.trim().is_empty(), rest.is_empty()) {
+        (true, _) => rest,
+        (false, true) => previous.trim().to_string(),
+        (false, false) => format!("{} {rest}", previous.trim()),
+    }
+}
+
+fn set_transcription_stage(state: &Arc<AppRuntime>, stage: Option<TranscriptionStage>) {
+    if let Ok(mut current) = state.transcription_stage.lock() {
+        *current = stage;
+    }
+}
+
+/// Emits `PROGRESS_EVENT` every `interval` until the returned sender is
+/// dropped.
+fn spawn_progress_ticker(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    interval: Duration,
+) -> Sender<()> {
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let app = app.clone();
+    let state = state.clone();
+    let started_at = Instant::now();
+
+    thread::spawn(move || {
+        while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+            let stage = state
+                .transcription_stage
+                .lock()
+                .ok()
+                .and_then(|stage| *stage);
+            let _ = app.emit(
+                PROGRESS_EVENT,
+                TranscriptionProgress {
+                    stage,
+                    elapsed_ms: started_at.elapsed().as_millis() as u64,
+                },
+            );
+        }
+    });
+
+    stop_tx
+}
+
+/// Expands the `\n`, `\t` and `\\` escapes allowed in `append_suffix`.
+/// Any other backslash is kept as typed.
+fn unescape_suffix(raw: &str) -> String {
+    let mut expanded = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            expanded.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => expanded.push('\n'),
+            Some('t') => expanded.push('\t'),
+            Some('\\') => expanded.push('\\'),
+            Some(other) => {
+                expanded.push('\\');
+                expanded.push(other);
+            }
+            None => expanded.push('\\'),
+        }
+    }
+    expanded
+}
+
+/// Fills every `{text}` in `paste_template` with the transcript.
+fn apply_paste_template(template: Option<&str>, text: &str) -> String {
+    match template {
+        Some(template) => unescape_suffix(template).replace(TEMPLATE_PLACEHOLDER, text),
+        None => text.to_string(),
+    }
+}
+
+/// Drops whole-word fillers ("um," but never "umbrella") and collapses
+/// whitespace. Sentence punctuation on a dropped filler moves to the previous
+/// word, and a sentence that started with a filler is re-capitalized.
+fn strip_filler_words(text: &str, fillers: &[String]) -> String {
+    let mut kept: Vec<String> = Vec::new();
+    let mut capitalize_next = false;
+
+    for token in text.split_whitespace() {
+        let word = token.trim_matches(|ch: char| !ch.is_alphanumeric());
+        let is_filler = !word.is_empty()
+            && fillers
+                .iter()
+                .any(|filler| filler.eq_ignore_ascii_case(word));
+
+        if !is_filler {
+            let mut token = token.to_string();
+            if std::mem::take(&mut capitalize_next) {
+                if let Some(first) = token.chars().next() {
+                    token.replace_range(..first.len_utf8(), &first.to_uppercase().to_string());
+                }
+            }
+            kept.push(token);
+            continue;
+        }
+
+        let sentence_start = match kept.last() {
+            Some(previous) => previous.ends_with(['.', '!', '?']),
+            None => true,
+        };
+        capitalize_next |= sentence_start && token.starts_with(|ch: char| ch.is_uppercase());
+
+        if let Some(end) = token
+            .chars()
+            .last()
+            .filter(|ch| matches!(ch, '.' | '!' | '?'))
+        {
+            if let Some(previous) = kept.last_mut() {
+                if !previous.ends_with(|ch: char| ch.is_ascii_punctuation()) {
+                    previous.push(end);
+                }
+            }
+        }
+    }
+
+    kept.join(" ")
+}
+
+/// Replaces spoken commands ("new line", "comma") with the text they stand
+/// for. Phrases only match whole words, so "periodic" is left alone, and
+/// punctuation the model put around a spoken command is dropped. Commands
+/// that end a sentence must also sit at a sentence boundary (end of text,
+/// before a capitalized word or another command, or punctuated by the
+/// model), which keeps "the trial period ended" intact.
+fn replace_voice_commands(text: &str, commands: &[VoiceCommand]) -> String {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut commands: Vec<(Vec<&str>, String)> = commands
+        .iter()
+        .filter_map(|command| {
+            let words: Vec<&str> = command.phrase.split_whitespace().collect();
+            (!words.is_empty()).then(|| (words, unescape_suffix(&command.output)))
+        })
+        .collect();
+    // Longest first, so "new paragraph" wins over a shorter phrase it starts
+    // with.
+    commands.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+    let starts_command = |token: &str| {
+        let word = token.trim_matches(|ch: char| !ch.is_alphanumeric());
+        commands
+            .iter()
+            .any(|(words, _)| words[0].eq_ignore_ascii_case(word))
+    };
+
+    let mut output = String::with_capacity(text.len());
+    let mut capitalize_next = false;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let matched = commands.iter().find(|(words, replacement)| {
+            let Some(window) = tokens.get(index..index + words.len()) else {
+                return false;
+            };
+            let spoken = window.iter().zip(words).all(|(token, word)| {
+                token
+                    .trim_matches(|ch: char| !ch.is_alphanumeric())
+                    .eq_ignore_ascii_case(word)
+            });
+            if !spoken || !replacement.trim_end().ends_with(['.', '!', '?']) {
+                return spoken;
+            }
+
+            let punctuated = window
+                .last()
+                .is_some_and(|token| token.ends_with(|ch: char| ch.is_ascii_punctuation()));
+            let next_starts_sentence = tokens
+                .get(index + words.len())
+                .is_none_or(|next| next.starts_with(char::is_uppercase) || starts_command(next));
+            punctuated || next_starts_sentence
+        });
+
+        if let Some((words, replacement)) = matched {
+            let kept = if replacement.starts_with(char::is_whitespace) {
+                output.trim_end_matches(' ').len()
+            } else {
+                output
+                    .trim_end_matches([' ', '.', ',', ';', ':', '!', '?'])
+                    .len()
+            };
+            output.truncate(kept);
+            output.push_str(replacement);
+            capitalize_next = replacement
+                .trim_end_matches(' ')
+                .ends_with(['.', '!', '?', '\n']);
+            index += words.len();
+            continue;
+        }
+
+        let mut token = tokens[index].to_string();
+        if std::mem::take(&mut capitalize_next) {
+            if let Some(first) = token.chars().next() {
+                token.replace_range(..first.len_utf8(), &first.to_uppercase().to_string());
+            }
+        }
+        if !output.is_empty() && !output.ends_with(char::is_whitespace) {
+            output.push(' ');
+        }
+        output.push_str(&token);
+        index += 1;
+    }
+
+    output
+}
+
+/// Pipes the transcript through a user shell command and returns its
+/// stdout. Gives up after `POST_PROCESS_TIMEOUT`.
+fn post_process_transcript(command_line: &str, transcript: &str) -> Result<String, String> {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    };
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    configure_child_process(&mut command);
+
+    let mut child = command
+        .spawn()
+        .map_err(|err| format!("Failed to launch post-process command: {err}"))?;
+
+    // Feed stdin and drain both pipes on their own threads so a command
+    // that writes before reading all input can't deadlock.
+    let stdin_writer = child.stdin.take().map(|mut stdin| {
+        let input = transcript.to_string();
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        })
+    });
+    let readers = [
+        child.stdout.take().map(|mut stdout| {
+            thread::spawn(move || {
+                let mut buffer = Vec::new();
+                let _ = stdout.read_to_end(&mut buffer);
+                buffer
+            })
+        }),
+        child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut buffer = Vec::new();
+                let _ = stderr.read_to_end(&mut buffer);
+                buffer
+            })
+        }),
+    ];
+
+    let deadline = Instant::now() + POST_PROCESS_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Post-process command timed out after {}s",
+                    POST_PROCESS_TIMEOUT.as_secs()
+                ));
+            }
+            Ok(None) => thread::sleep(POST_PROCESS_POLL_INTERVAL),
+            Err(err) => return Err(format!("Failed to wait for post-process command: {err}")),
+        }
+    };
+
+    if let Some(writer) = stdin_writer {
+        let _ = writer.join();
+    }
+    let [stdout, stderr] = readers.map(|reader| {
+        reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default()
+    });
+
+    if !status.success() {
+        return Err(command_error("Post-process command failed", &stderr));
+    }
+
+    let processed = String::from_utf8_lossy(&stdout).trim().to_string();
+    if processed.is_empty() {
+        return Err("Post-process command printed no text".to_string());
+    }
+
+    Ok(processed)
+}
+
+fn copy_transcript_to_clipboard(transcript: &str) -> Result<(), String> {
+    if transcript.is_empty() {
+        return Ok(());
+    }
+
+    let mut clipboard = Clipboard::new().map_err(|err| format!("Clipboard init failed: {err}"))?;
+    clipboard
+        .set_text(transcript.to_string())
+        .map_err(|err| format!("Failed to write transcript to clipboard: {err}"))
+}
+
+#[cfg(target_os = "macos")]
+fn secure_input_active() -> bool {
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn IsSecureEventInputEnabled() -> u8;
+    }
+
+    // SAFETY: takes no arguments and only reads global input state.
+    unsafe { IsSecureEventInputEnabled() != 0 }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn secure_input_active() -> bool {
+    false
+}
+
+fn new_enigo() -> Result<Enigo, String> {
+    Enigo::new(&Settings::default()).map_err(|err| format!("{INPUT_AUTOMATION_UNAVAILABLE}: {err}"))
+}
+
+/// Whether a failed injection leaves the transcript on the clipboard. Init
+/// failures always do: nothing was typed, so the text would otherwise be
+/// lost.
+fn keeps_on_clipboard(settings: &AppSettings, err: &str) -> bool {
+    settings.clipboard_fallback || err.starts_with(INPUT_AUTOMATION_UNAVAILABLE)
+}
+
+fn fallback_message(err: &str) -> String {
+    if err.starts_with(INPUT_AUTOMATION_UNAVAILABLE) {
+        return format!("{err}. Text is on your clipboard, paste it manually");
+    }
+
+    format!("Injection may have failed ({err}). Text is on your clipboard")
+}
+
+/// Windows shortcut handlers match virtual-key codes, and a `Unicode('v')`
+/// press can arrive as a character packet or on the wrong key under AZERTY
+/// or Dvorak, so the paste goes out as `VK_V` directly.
+#[cfg(windows)]
+const PASTE_KEY: Key = Key::Other(0x56);
+
+/// macOS and X11 resolve the character through the active layout, which
+/// is what their paste shortcuts match on.
+#[cfg(not(windows))]
+const PASTE_KEY: Key = Key::Unicode('v');
+
+#[cfg(target_os = "macos")]
+const PASTE_MODIFIER: Key = Key::Meta;
+
+#[cfg(not(target_os = "macos"))]
+const PASTE_MODIFIER: Key = Key::Control;
+
+fn send_paste_shortcut() -> Result<(), String> {
+    if secure_input_active() {
+        return Err("secure input is active".to_string());
+    }
+
+    let mut enigo = new_enigo()?;
+
+    thread::sleep(PRE_PASTE_DELAY);
+
+    enigo
+        .key(PASTE_MODIFIER, Press)
+        .and_then(|_| enigo.key(PASTE_KEY, Click))
+        .and_then(|_| enigo.key(PASTE_MODIFIER, Release))
+        .map_err(|err| format!("Failed to paste transcript: {err}"))
+}
+
+fn paste_text_at_cursor(transcript: &str, settings: &AppSettings) -> Result<(), String> {
+    let mut clipboard = Clipboard::new().map_err(|err| format!("Clipboard init failed: {err}"))?;
+    let previous_clipboard = settings
+        .restores_clipboard()
+        .then(|| clipboard.get_text().ok())
+        .flatten();
+    clipboard
+        .set_text(transcript.to_string())
+        .map_err(|err| format!("Failed to write transcript to clipboard: {err}"))?;
+
+    if let Err(err) = send_paste_shortcut() {
+        // Keep the transcript on the clipboard so a blocked paste never
+        // loses the dictation.
+        if keeps_on_clipboard(settings, &err) {
+            return Err(fallback_message(&err));
+        }
+
+        if let Some(previous) = previous_clipboard {
+            let _ = clipboard.set_text(previous);
+        }
+        return Err(err);
+    }
+
+    if !settings.restores_clipboard() {
+        return Ok(());
+    }
+
+    thread::sleep(Duration::from_millis(settings.injection_delay_ms));
+
+    if let Some(previous) = previous_clipboard {
+        let _ = clipboard.set_text(previous);
+    }
+
+    Ok(())
+}
+
+/// Types `text` in chunks of `type_chunk_size` characters, pausing between
+/// them, since some apps drop characters from one long burst.
+fn type_in_chunks(enigo: &mut Enigo, text: &str, settings: &AppSettings) -> Result<(), String> {
+    if settings.type_chunk_size == 0 {
+        return enigo
+            .text(text)
+            .map_err(|err| format!("Failed to type transcript: {err}"));
+    }
+
+    let chars = text.chars().collect::<Vec<_>>();
+    for (index, chunk) in chars.chunks(settings.type_chunk_size).enumerate() {
+        if index > 0 {
+            thread::sleep(Duration::from_millis(settings.type_chunk_delay_ms));
+        }
+
+        enigo
+            .text(&chunk.iter().collect::<String>())
+            .map_err(|err| format!("Failed to type transcript: {err}"))?;
+    }
+
+    Ok(())
+}
+
+fn type_text_at_cursor(transcript: &str, settings: &AppSettings) -> Result<(), String> {
+    if !settings.restores_clipboard() {
+        copy_transcript_to_clipboard(transcript)?;
+    }
+
+    let typed = if secure_input_active() {
+        Err("secure input is active".to_string())
+    } else {
+        new_enigo().and_then(|mut enigo| {
+            thread::sleep(PRE_PASTE_DELAY);
+            type_in_chunks(&mut enigo, transcript, settings)
+        })
+    };
+
+    match typed {
+        Err(err) if keeps_on_clipboard(settings, &err) => {
+            copy_transcript_to_clipboard(transcript)?;
+            Err(fallback_message(&err))
+        }
+        other => other,
+    }
+}
+
+fn inject_text_at_cursor(transcript: &str, settings: &AppSettings) -> Result<(), String> {
+    if transcript.is_empty() {
+        return Ok(());
+    }
+
+    if !settings.auto_inject {
+        return match settings.output_mode {
+            OutputMode::Both => copy_transcript_to_clipboard(transcript),
+            _ => Ok(()),
+        };
+    }
+
+    match settings.injection_method {
+        InjectionMethod::Paste => paste_text_at_cursor(transcript, settings),
+        InjectionMethod::Typing => type_text_at_cursor(transcript, settings),
+    }
+}
+
+fn normalize_app_name(name: &str) -> String {
+    let file_name = Path::new(name.trim())
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    file_name
+        .strip_suffix(".exe")
+        .map(str::to_string)
+        .unwrap_or(file_name)
+}
+
+#[cfg(windows)]
+fn foreground_app_name() -> Option<String> {
+    use std::{ffi::c_void, ffi::OsString, os::windows::ffi::OsStringExt};
+
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> *mut c_void;
+        fn GetWindowThreadProcessId(window: *mut c_void, process_id: *mut u32) -> u32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+        fn QueryFullProcessImageNameW(
+            process: *mut c_void,
+            flags: u32,
+            exe_name: *mut u16,
+            size: *mut u32,
+        ) -> i32;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+
+    // SAFETY: plain Win32 queries on handles we own; the process handle is
+    // closed before returning and the buffer length is passed explicitly.
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(window, &mut process_id);
+        if process_id == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id);
+        if process.is_null() {
+            return None;
+        }
+
+        let mut buffer = [0u16; 1024];
+        let mut size = buffer.len() as u32;
+        let queried = QueryFullProcessImageNameW(process, 0, buffer.as_mut_ptr(), &mut size);
+        CloseHandle(process);
+
+        if queried == 0 {
+            return None;
+        }
+
+        let path = PathBuf::from(OsString::from_wide(&buffer[..size as usize]));
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn foreground_app_name() -> Option<String> {
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let process_id: u32 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    fs::read_to_string(format!("/proc/{process_id}/comm"))
+        .ok()
+        .map(|name| name.trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn foreground_app_name() -> Option<String> {
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to get name of first application process whose frontmost is true",
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn foreground_app_name() -> Option<String> {
+    None
+}
+
+/// Identifies the foreground window, so two windows of one app differ.
+#[cfg(windows)]
+fn foreground_window_id() -> Option<u64> {
+    use std::ffi::c_void;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> *mut c_void;
+    }
+
+    // SAFETY: read-only Win32 query; the handle is only compared, never used.
+    let window = unsafe { GetForegroundWindow() };
+    (!window.is_null()).then_some(window as usize as u64)
+}
+
+#[cfg(target_os = "linux")]
+fn foreground_window_id() -> Option<u64> {
+    let output = Command::new("xdotool")
+        .arg("getactivewindow")
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&window| window != 0)
+}
+
+/// System Events exposes no stable window id, so macOS compares apps only.
+#[cfg(not(any(windows, target_os = "linux")))]
+fn foreground_window_id() -> Option<u64> {
+    None
+}
+
+/// Where a dictation started, for the focus guard.
+#[derive(Debug, Clone, Default)]
+struct FocusTarget {
+    app: Option<String>,
+    window: Option<u64>,
+}
+
+impl FocusTarget {
+    fn capture() -> Self {
+        Self {
+            app: foreground_app_name(),
+            window: foreground_window_id(),
+        }
+    }
+
+    fn is_known(&self) -> bool {
+        self.app.is_some() || self.window.is_some()
+    }
+
+    /// Compares window ids when both sides have one, so another window of
+    /// the same app doesn't pass; otherwise falls back to the app name.
+    fn matches(&self, current: &FocusTarget) -> bool {
+        if let (Some(target), Some(window)) = (self.window, current.window) {
+            return target == window;
+        }
+
+        match (&self.app, &current.app) {
+            (Some(target), Some(app)) => normalize_app_name(target) == normalize_app_name(app),
+            (Some(_), None) => false,
+            (None, _) => self.window.is_none(),
+        }
+    }
+}
+
+/// Whether a window that could take text has focus; `None` when the
+/// platform can't tell.
+#[cfg(windows)]
+fn has_focused_window() -> Option<bool> {
+    use std::ffi::c_void;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> *mut c_void;
+        fn GetClassNameW(window: *mut c_void, class_name: *mut u16, max_count: i32) -> i32;
+    }
+
+    // SAFETY: read-only Win32 queries; the buffer length is passed explicitly.
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return Some(false);
+        }
+
+        let mut buffer = [0u16; 256];
+        let len = GetClassNameW(window, buffer.as_mut_ptr(), buffer.len() as i32);
+        if len <= 0 {
+            return None;
+        }
+
+        // The desktop and the taskbar take focus when clicked but never text.
+        let class_name = String::from_utf16_lossy(&buffer[..len as usize]);
+        Some(!matches!(
+            class_name.as_str(),
+            "Progman" | "WorkerW" | "Shell_TrayWnd"
+        ))
+    }
+}
+
+/// xdotool also fails on Wayland or under window managers without
+/// `_NET_ACTIVE_WINDOW`, so only a successful query for window 0 counts as
+/// nothing focused.
+#[cfg(target_os = "linux")]
+fn has_focused_window() -> Option<bool> {
+    let output = Command::new("xdotool")
+        .arg("getactivewindow")
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let window: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(window != 0)
+}
+
+/// Finder stays frontmost after a click on the desktop, but without a window.
+#[cfg(target_os = "macos")]
+fn has_focused_window() -> Option<bool> {
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to count windows of (first application process whose frontmost is true)",
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let count: u32 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(count > 0)
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn has_focused_window() -> Option<bool> {
+    None
+}
+
+fn settings_for_app(settings: &AppSettings, app_name: Option<&str>) -> AppSettings {
+    let mut resolved = settings.clone();
+
+    let Some(app_name) = app_name.map(normalize_app_name) else {
+        return resolved;
+    };
+
+    if let Some(profile) = settings
+        .app_profiles
+        .iter()
+        .find(|profile| normalize_app_name(&profile.app) == app_name)
+    {
+        resolved.injection_method = profile.injection_method;
+        resolved.injection_delay_ms = profile.injection_delay_ms;
+    }
+
+    resolved
+}
+
+/// Explains why text must not be injected into `current`, if it must not.
+fn injection_block_reason(
+    settings: &AppSettings,
+    target: &FocusTarget,
+    current: &FocusTarget,
+) -> Option<String> {
+    if let Some(current) = current.app.as_deref().map(normalize_app_name) {
+        if settings
+            .injection_denylist
+            .iter()
+            .any(|app| normalize_app_name(app) == current)
+        {
+            return Some(format!("Injection is blocked in {current}"));
+        }
+    }
+
+    if settings.unfocused_guard && has_focused_window() == Some(false) {
+        return Some("No window is focused".to_string());
+    }
+
+    if !settings.focus_guard || target.matches(current) {
+        return None;
+    }
+
+    Some(match target.app.as_deref().map(normalize_app_name) {
+        Some(app) if current.app.as_deref().map(normalize_app_name) == Some(app.clone()) => {
+            format!("Focus moved to another {app} window")
+        }
+        Some(app) => format!("Focus moved away from {app}"),
+        None => "Focus moved away from the dictation window".to_string(),
+    })
+}
+
+/// Injects into the `current` window unless a guard blocks it, in which
+/// case the text only lands on the clipboard.
+fn inject_unless_blocked(
+    settings: &AppSettings,
+    target: &FocusTarget,
+    current: &FocusTarget,
+    text: &str,
+) -> (DeliveryMethod, Result<(), String>) {
+    match injection_block_reason(settings, target, current) {
+        Some(reason) => (
+            DeliveryMethod::Clipboard,
+            copy_transcript_to_clipboard(text)
+                .and_then(|_| Err(format!("{reason}. Text is on your clipboard"))),
+        ),
+        None => {
+            let app_settings = settings_for_app(settings, current.app.as_deref());
+            (
+                DeliveryMethod::for_settings(&app_settings),
+                inject_text_at_cursor(text, &app_settings),
+            )
+        }
+    }
+}
+
+fn emit_injection_result(
+    app: &AppHandle,
+    method: DeliveryMethod,
+    target_app: Option<String>,
+    delivered: &Result<(), String>,
+) {
+    let _ = app.emit(
+        INJECTION_EVENT,
+        InjectionResult {
+            success: delivered.is_ok(),
+            method,
+            target_app,
+            error: delivered.as_ref().err().cloned(),
+        },
+    );
+}
+
+fn show_settings_window(app: &AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    window
+        .show()
+        .map_err(|err| format!("Failed to show main window: {err}"))?;
+    window
+        .set_focus()
+        .map_err(|err| format!("Failed to focus main window: {err}"))
+}
+
+/// Created on demand; closing the window destroys it.
+fn show_history_window(app: &AppHandle) -> Result<(), String> {
+    let window = match app.get_webview_window(HISTORY_LABEL) {
+        Some(window) => window,
+        None => WebviewWindowBuilder::new(
+            app,
+            HISTORY_LABEL,
+            WebviewUrl::App("index.html?history=1".into()),
+        )
+        .title("Transcript History")
+        .inner_size(560.0, 640.0)
+        .center()
+        .build()
+        .map_err(|err| format!("Failed to create history window: {err}"))?,
+    };
+
+    window
+        .show()
+        .map_err(|err| format!("Failed to show history window: {err}"))?;
+    window
+        .set_focus()
+        .map_err(|err| format!("Failed to focus history window: {err}"))
+}
+
+fn hide_settings_window(app: &AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    window
+        .hide()
+        .map_err(|err| format!("Failed to hide main window: {err}"))
+}
+
+/// `"light"` or `"dark"`. `System` follows the OS theme as reported by the
+/// main window, falling back to dark.
+fn effective_theme(app: &AppHandle, preference: ThemePreference) -> &'static str {
+    let theme = match preference {
+        ThemePreference::Light => Theme::Light,
+        ThemePreference::Dark => Theme::Dark,
+        ThemePreference::System => app
+            .get_webview_window("main")
+            .and_then(|window| window.theme().ok())
+            .unwrap_or(Theme::Dark),
+    };
+
+    match theme {
+        Theme::Light => "light",
+        _ => "dark",
+    }
+}
+
+fn emit_theme(app: &AppHandle, preference: ThemePreference) {
+    let _ = app.emit(THEME_EVENT, effective_theme(app, preference));
+}
+
+fn ensure_overlay_window(app: &AppHandle, theme: &str) -> Result<(), String> {
+    if app.get_webview_window(OVERLAY_LABEL).is_some() {
+        return Ok(());
+    }
+
+    // The theme rides along in the URL so the first frame already matches.
+    let _window = WebviewWindowBuilder::new(
+        app,
+        OVERLAY_LABEL,
+        WebviewUrl::App(format!("index.html?overlay=1&theme={theme}").into()),
+    )
+    .title("Dictation Overlay")
+    .inner_size(280.0, 72.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .focusable(false)
+    .skip_taskbar(true)
+    .visible(false)
+    .build()
+    .map_err(|err| format!("Failed to create overlay window: {err}"))?;
+
+    Ok(())
+}
+
+/// The overlay stays unfocusable either way, so clicking it never pulls
+/// focus away from the app the transcript goes into.
+fn set_overlay_clickable(app: &AppHandle, clickable: bool) -> Result<(), String> {
+    let Some(overlay) = app.get_webview_window(OVERLAY_LABEL) else {
+        return Ok(());
+    };
+
+    overlay
+        .set_ignore_cursor_events(!clickable)
+        .map_err(|err| format!("Failed to update overlay click handling: {err}"))
+}
+
+/// Creates or closes the overlay to match `show_overlay`. Status emission
+/// skips the overlay whenever its window doesn't exist.
+fn sync_overlay_window(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    if !settings.show_overlay {
+        if let Some(overlay) = app.get_webview_window(OVERLAY_LABEL) {
+            overlay
+                .close()
+                .map_err(|err| format!("Failed to close overlay window: {err}"))?;
+        }
+        return Ok(());
+    }
+
+    ensure_overlay_window(app, effective_theme(app, settings.theme))?;
+    set_overlay_clickable(app, settings.overlay_clickable)
+}
+
+fn ensure_review_window(app: &AppHandle) -> Result<(), String> {
+    if app.get_webview_window(REVIEW_LABEL).is_some() {
+        return Ok(());
+    }
+
+    let _window = WebviewWindowBuilder::new(
+        app,
+        REVIEW_LABEL,
+        WebviewUrl::App("index.html?review=1".into()),
+    )
+    .title("Review Transcript")
+    .inner_size(440.0, 240.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .visible(false)
+    .build()
+    .map_err(|err| format!("Failed to create review window: {err}"))?;
+
+    Ok(())
+}
+
+/// Centre of the foreground window, in the desktop coordinates the platform
+/// reports: physical pixels on Windows and X11, points on macOS.
+#[cfg(windows)]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    struct Rect {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> *mut c_void;
+        fn GetWindowRect(window: *mut c_void, rect: *mut Rect) -> i32;
+    }
+
+    // SAFETY: read-only Win32 queries; `rect` outlives the call.
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+
+        let mut rect = Rect {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        if GetWindowRect(window, &mut rect) == 0 {
+            return None;
+        }
+
+        Some((
+            (rect.left + rect.right) as f64 / 2.0,
+            (rect.top + rect.bottom) as f64 / 2.0,
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowgeometry", "--shell"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let geometry = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| -> Option<f64> {
+        geometry
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))?
+            .trim()
+            .parse()
+            .ok()
+    };
+
+    Some((
+        value("X")? + value("WIDTH")? / 2.0,
+        value("Y")? + value("HEIGHT")? / 2.0,
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to tell (first application process whose frontmost is true) to get {position, size} of front window",
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let values: Vec<f64> = String::from_utf8_lossy(&output.stdout)
+        .split(',')
+        .filter_map(|value| value.trim().parse().ok())
+        .collect();
+    let [x, y, width, height] = values[..] else {
+        return None;
+    };
+
+    Some((x + width / 2.0, y + height / 2.0))
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    None
+}
+
+/// Monitor showing the foreground window, where injected text will land.
+fn foreground_monitor(window: &WebviewWindow) -> Option<Monitor> {
+    let (x, y) = foreground_window_center()?;
+
+    window
+        .available_monitors()
+        .ok()?
+        .into_iter()
+        .find(|monitor| {
+            // macOS reports the window in points rather than pixels.
+            let scale = if cfg!(target_os = "macos") {
+                monitor.scale_factor()
+            } else {
+                1.0
+            };
+            let left = monitor.position().x as f64 / scale;
+            let top = monitor.position().y as f64 / scale;
+            let width = monitor.size().width as f64 / scale;
+            let height = monitor.size().height as f64 / scale;
+
+            (left..left + width).contains(&x) && (top..top + height).contains(&y)
+        })
+}
+
+/// Centres the overlay near the bottom of a monitor. `follow_focus` picks
+/// the monitor again from `overlay_monitor`; otherwise the overlay stays on
+/// the one it is already on.
+fn place_overlay_bottom_center(app: &AppHandle, follow_focus: bool) {
+    let Some(window) = app.get_webview_window(OVERLAY_LABEL) else {
+        return;
+    };
+
+    let placement = app
+        .try_state::<Arc<AppRuntime>>()
+        .and_then(|state| {
+            let settings = state.settings.lock().ok()?;
+            Some(settings.overlay_monitor)
+        })
+        .unwrap_or(OverlayMonitor::FocusedWindow);
+    let chosen = match (follow_focus, placement) {
+        (false, _) => window.current_monitor().ok().flatten(),
+        (true, OverlayMonitor::FocusedWindow) => {
+            foreground_monitor(&window).or_else(|| window.current_monitor().ok().flatten())
+        }
+        (true, OverlayMonitor::Primary) => None,
+    };
+    let monitor = chosen.or_else(|| window.primary_monitor().ok().flatten());
+
+    let Some(monitor) = monitor else {
+        return;
+    };
+
+    let work_area = monitor.work_area();
+    let overlay_size = match window.inner_size() {
+        Ok(size) => size,
+        Err(_) => return,
+    };
+
+    let x = work_area.position.x + ((work_area.size.width as i32 - overlay_size.width as i32) / 2);
+    let y = work_area.position.y + ((work_area.size.height as f32 * 0.90) as i32)
+        - (overlay_size.height as i32 / 2);
+
+    let _ = window.set_position(Position::Physical(PhysicalPosition::new(x, y)));
+}
+
+/// Badges the tray icon while dictation is active or has failed.
+fn update_tray_icon(app: &AppHandle, phase: &DictationPhase) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let icon = match phase {
+        DictationPhase::Listening => Some(include_image!("icons/tray-listening.png")),
+        DictationPhase::Transcribing => Some(include_image!("icons/tray-transcribing.png")),
+        DictationPhase::Error => Some(include_image!("icons/tray-error.png")),
+        DictationPhase::Idle | DictationPhase::Bootstrapping => app.default_window_icon().cloned(),
+    };
+
+    if let Err(err) = tray.set_icon(icon) {
+        eprintln!("failed to update tray icon: {err}");
+    }
+}
+
+/// Invalidates any pending delayed overlay hide and returns the new token.
+fn next_overlay_hide_generation(app: &AppHandle) -> Option<u64> {
+    let state = app.try_state::<Arc<AppRuntime>>()?;
+    let mut generation = state.overlay_hide_generation.lock().ok()?;
+    *generation += 1;
+    Some(*generation)
+}
+
+fn emit_status(app: &AppHandle, phase: DictationPhase, message: Option<String>) {
+    emit_status_with_linger(app, phase, message, Duration::ZERO);
+}
+
+/// Tells the UI a trigger was ignored. While idle the overlay is hidden, so
+/// it briefly shows the reason; otherwise the current phase stays on screen.
+fn emit_blocked(app: &AppHandle, state: &Arc<AppRuntime>, reason: &str) {
+    let _ = app.emit(BLOCKED_EVENT, reason.to_string());
+
+    if current_phase(state).ok() == Some(RuntimePhase::Idle) {
+        emit_status_with_linger(
+            app,
+            DictationPhase::Idle,
+            Some(reason.to_string()),
+            BLOCKED_LINGER,
+        );
+    }
+}
+
+/// Like `emit_status`, but an `Idle` overlay stays up for `linger` and only
+/// hides if nothing else was shown in the meantime.
+fn emit_status_with_linger(
+    app: &AppHandle,
+    phase: DictationPhase,
+    message: Option<String>,
+    linger: Duration,
+) {
+    emit_status_payload(
+        app,
+        DictationStatus {
+            phase,
+            message,
+            stage: None,
+            overlay_text: None,
+        },
+        linger,
+    );
+}
+
+/// Fills in the user's `overlay_messages` template for the payload's phase.
+/// Only the overlay gets it; the main window keeps the built-in message.
+fn apply_overlay_message(app: &AppHandle, mut payload: DictationStatus) -> DictationStatus {
+    let template = app.try_state::<Arc<AppRuntime>>().and_then(|state| {
+        let template = state
+            .settings
+            .lock()
+            .ok()?
+            .overlay_messages
+            .get(&payload.phase)
+            .cloned();
+        template
+    });
+
+    if let Some(template) = template {
+        let message = payload.message.as_deref().unwrap_or_default();
+        payload.overlay_text = Some(template.replace(MESSAGE_PLACEHOLDER, message));
+    }
+    payload
+}
+
+fn emit_status_payload(app: &AppHandle, payload: DictationStatus, linger: Duration) {
+    let phase = payload.phase.clone();
+    let _ = app.emit(DICTATION_EVENT, payload.clone());
+    update_tray_icon(app, &phase);
+
+    if let Some(overlay) = app.get_webview_window(OVERLAY_LABEL) {
+        let _ = overlay.emit(DICTATION_EVENT, apply_overlay_message(app, payload));
+        let generation = next_overlay_hide_generation(app);
+
+        match phase {
+            DictationPhase::Idle if linger.is_zero() => {
+                let _ = overlay.hide();
+            }
+            DictationPhase::Idle => {
+                place_overlay_bottom_center(app, false);
+                let _ = overlay.show();
+
+                let app = app.clone();
+                thread::spawn(move || {
+                    thread::sleep(linger);
+
+                    let Some(state) = app.try_state::<Arc<AppRuntime>>() else {
+                        return;
+                    };
+                    let current = state
+                        .overlay_hide_generation
+                        .lock()
+                        .ok()
+                        .map(|current| *current);
+                    if current == generation
+                        && current_phase(&state).ok() == Some(RuntimePhase::Idle)
+                    {
+                        let _ = overlay.hide();
+                    }
+                });
+            }
+            _ => {
+                place_overlay_bottom_center(app, matches!(phase, DictationPhase::Listening));
+                let _ = overlay.show();
+            }
+        }
+    }
+}
+
+fn set_phase(state: &Arc<AppRuntime>, phase: RuntimePhase) -> Result<(), String> {
+    *state
+        .phase
+        .lock()
+        .map_err(|_| "Failed to lock runtime phase".to_string())? = phase;
+    Ok(())
+}
+
+fn current_phase(state: &Arc<AppRuntime>) -> Result<RuntimePhase, String> {
+    state
+        .phase
+        .lock()
+        .map(|phase| *phase)
+        .map_err(|_| "Failed to lock runtime phase".to_string())
+}
+
+/// Keeps the runtime out of Idle while work outside the dictation worker
+/// uses the sidecar or the microphone, so a dictation can't start on top of
+/// it. Dropping it restores Idle and starts a dictation queued meanwhile.
+struct BusyPhase {
+    app: AppHandle,
+    state: Arc<AppRuntime>,
+}
+
+impl BusyPhase {
+    /// Fails with `busy` unless the runtime is idle.
+    fn claim(app: &AppHandle, state: &Arc<AppRuntime>, busy: &str) -> Result<Self, AppError> {
+        let mut phase = state
+            .phase
+            .lock()
+            .map_err(|_| "Failed to lock runtime phase".to_string())?;
+        if *phase != RuntimePhase::Idle {
+            return Err(busy.to_string().into());
+        }
+        *phase = RuntimePhase::Transcribing;
+
+        Ok(Self {
+            app: app.clone(),
+            state: state.clone(),
+        })
+    }
+}
+
+impl Drop for BusyPhase {
+    fn drop(&mut self) {
+        let _ = set_phase(&self.state, RuntimePhase::Idle);
+        if take_queued_start(&self.state) {
+            let _ = queue_command(&self.app, &self.state, WorkerCommand::Start);
+        }
+    }
+}
+
+fn set_runtime_ready(state: &Arc<AppRuntime>, ready: bool) -> Result<(), String> {
+    *state
+        .ready
+        .lock()
+        .map_err(|_| "Failed to lock runtime readiness".to_string())? = ready;
+    Ok(())
+}
+
+fn is_runtime_ready(state: &Arc<AppRuntime>) -> Result<bool, String> {
+    state
+        .ready
+        .lock()
+        .map(|ready| *ready)
+        .map_err(|_| "Failed to lock runtime readiness".to_string())
+}
+
+fn record_timing(state: &Arc<AppRuntime>, kind: TimingKind, elapsed: Duration) {
+    if let Ok(mut history) = state.performance.lock() {
+        history.record(kind, elapsed);
+    }
+}
+
+/// Parks a transcript for review instead of injecting it right away. One
+/// that arrives while another is still open waits its turn.
+fn hold_for_review(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    text: &str,
+    target: FocusTarget,
+) -> Result<(), String> {
+    let waiting = {
+        let mut pending = state
+            .pending_transcripts
+            .lock()
+            .map_err(|_| "Failed to lock pending transcript".to_string())?;
+        pending.push_back(PendingTranscript {
+            text: text.to_string(),
+            target,
+        });
+        pending.len()
+    };
+
+    if waiting > 1 {
+        return Ok(());
+    }
+    show_review_window(app, text)
+}
+
+fn show_review_window(app: &AppHandle, text: &str) -> Result<(), String> {
+    let window = app
+        .get_webview_window(REVIEW_LABEL)
+        .ok_or_else(|| "Review window not found".to_string())?;
+    let _ = window.emit(PENDING_EVENT, text.to_string());
+    window
+        .show()
+        .map_err(|err| format!("Failed to show review window: {err}"))?;
+    window
+        .set_focus()
+        .map_err(|err| format!("Failed to focus review window: {err}"))
+}
+
+/// Removes the transcript under review and hides the window, so focus can
+/// return to the app it goes into. `show_next_pending` brings up the next.
+fn take_pending_transcript(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+) -> Result<PendingTranscript, String> {
+    let pending = state
+        .pending_transcripts
+        .lock()
+        .map_err(|_| "Failed to lock pending transcript".to_string())?
+        .pop_front();
+
+    if let Some(window) = app.get_webview_window(REVIEW_LABEL) {
+        let _ = window.hide();
+    }
+
+    pending.ok_or_else(|| "No transcript is waiting for review".to_string())
+}
+
+fn show_next_pending(app: &AppHandle, state: &Arc<AppRuntime>) {
+    let next = state
+        .pending_transcripts
+        .lock()
+        .ok()
+        .and_then(|pending| pending.front().map(|next| next.text.clone()));
+
+    if let Some(next) = next {
+        if let Err(err) = show_review_window(app, &next) {
+            eprintln!("{err}");
+        }
+    }
+}
+
+/// Gives focus up to `REVIEW_FOCUS_TIMEOUT` to return to `target` after
+/// the review window hides. Without a known target it waits a fixed
+/// `REVIEW_FOCUS_DELAY`.
+fn wait_for_focus(target: &FocusTarget) {
+    if !target.is_known() {
+        thread::sleep(REVIEW_FOCUS_DELAY);
+        return;
+    }
+
+    let deadline = Instant::now() + REVIEW_FOCUS_TIMEOUT;
+    while Instant::now() < deadline {
+        if target.matches(&FocusTarget::capture()) {
+            return;
+        }
+        thread::sleep(FOCUS_POLL_INTERVAL);
+    }
+}
+
+fn confirm_injection_internal(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    edited: Option<String>,
+) -> Result<(), AppError> {
+    if edited.as_deref().is_some_and(|text| text.trim().is_empty()) {
+        return Err("The transcript is empty. Discard it instead"
+            .to_string()
+            .into());
+    }
+    let pending = take_pending_transcript(app, state)?;
+    // Delivered as edited; trimming would drop the suffix and any trailing
+    // whitespace from the paste template.
+    let text = edited.unwrap_or(pending.text);
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+
+    // The focus guard checks the window the transcript was dictated into,
+    // as for direct injection.
+    wait_for_focus(&pending.target);
+
+    let foreground = FocusTarget::capture();
+    let (method, delivered) = inject_unless_blocked(&settings, &pending.target, &foreground, &text);
+    emit_injection_result(app, method, foreground.app, &delivered);
+
+    if let Err(err) = &delivered {
+        emit_status(app, DictationPhase::Error, Some(err.clone()));
+    }
+    show_next_pending(app, state);
+
+    delivered.map_err(|err| AppError::new(ErrorCode::InjectionFailed, err))
+}
+
+fn discard_transcript_internal(app: &AppHandle, state: &Arc<AppRuntime>) -> Result<(), String> {
+    take_pending_transcript(app, state)?;
+    show_next_pending(app, state);
+
+    if current_phase(state)? == RuntimePhase::Idle {
+        emit_status(
+            app,
+            DictationPhase::Idle,
+            Some("Transcript discarded".to_string()),
+        );
+    }
+
+    Ok(())
+}
+
+/// Kills the running transcription sidecar. This bypasses the worker queue,
+/// which drops commands while the worker is blocked transcribing.
+fn abort_transcription_internal(state: &Arc<AppRuntime>) -> Result<(), String> {
+    // Cancelling means stop, not "skip to the next recording".
+    take_queued_start(state);
+    let child = state
+        .transcription_child
+        .lock()
+        .map_err(|_| "Failed to lock transcription process".to_string())?
+        .take();
+    let Some(mut child) = child else {
+        return Err("No transcription is running".to_string());
+    };
+
+    child
+        .kill()
+        .map_err(|err| format!("Failed to stop transcription: {err}"))?;
+    let _ = child.wait();
+    Ok(())
+}
+
+fn worker_start(app: &AppHandle, state: &Arc<AppRuntime>, active: &mut Option<RecorderSession>) {
+    if active.is_some() {
+        return;
+    }
+
+    match current_phase(state) {
+        Ok(RuntimePhase::Transcribing) => return,
+        Ok(RuntimePhase::Listening) => return,
+        Ok(RuntimePhase::Idle) => {}
+        Err(err) => {
+            emit_status(app, DictationPhase::Error, Some(err));
+            return;
+        }
+    }
+
+    let settings = match state.settings.lock() {
+        Ok(settings) => settings.clone(),
+        Err(_) => {
+            emit_status(
+                app,
+                DictationPhase::Error,
+                Some("Failed to lock settings".to_string()),
+            );
+            return;
+        }
+    };
+
+    match is_runtime_ready(state) {
+        Ok(true) => {}
+        Ok(false) if settings.queue_while_bootstrapping => {}
+        Ok(false) => {
+            let _ = app.emit(BLOCKED_EVENT, "ASR runtime is not ready yet".to_string());
+            emit_status(
+                app,
+                DictationPhase::Bootstrapping,
+                Some(RUNTIME_NOT_READY.to_string()),
+            );
+            return;
+        }
+        Err(err) => {
+            emit_status(app, DictationPhase::Error, Some(err));
+            return;
+        }
+    }
+
+    let started_at = Instant::now();
+    let recorder = start_recorder_with_retries(app, state, &settings);
+    if recorder.is_ok() {
+        record_timing(state, TimingKind::RecorderStart, started_at.elapsed());
+    }
+
+    match recorder {
+        Ok(session) => {
+            *active = Some(session);
+            play_audio_cue(&settings, AudioCue::Start);
+            let _ = set_phase(state, RuntimePhase::Listening);
+            emit_status(
+                app,
+                DictationPhase::Listening,
+                Some("Listening...".to_string()),
+            );
+        }
+        Err(err) => {
+            let _ = set_phase(state, RuntimePhase::Idle);
+            emit_status(app, DictationPhase::Error, Some(err));
+        }
+    }
+}
+
+fn worker_stop(app: &AppHandle, state: &Arc<AppRuntime>, active: &mut Option<RecorderSession>) {
+    if current_phase(state).ok() != Some(RuntimePhase::Listening) {
+        return;
+    }
+
+    // Start and Stop share the worker channel and `worker_start` returns
+    // only once the stream is running, so a quick tap always finds the
+    // session here; it may just not have captured anything yet.
+    let Some(session) = active.take() else {
+        return;
+    };
+    let recording_elapsed = session.started_at.elapsed();
+    let target = session.target.clone();
+
+    if session
+        .captured()
+        .is_some_and(|captured| captured < MIN_RECORDING)
+    {
+        if let Ok(path) = session.finalize() {
+            let _ = fs::remove_file(path);
+        }
+        let _ = set_phase(state, RuntimePhase::Idle);
+        emit_status(
+            app,
+            DictationPhase::Idle,
+            Some("Recording too short".to_string()),
+        );
+        return;
+    }
+
+    let audio_path = match session.finalize() {
+        Ok(path) => path,
+        Err(err) => {
+            let _ = set_phase(state, RuntimePhase::Idle);
+            emit_status(app, DictationPhase::Error, Some(err));
+            return;
+        }
+    };
+
+    let settings = match state.settings.lock() {
+        Ok(settings) => settings.clone(),
+        Err(_) => {
+            let _ = set_phase(state, RuntimePhase::Idle);
+            emit_status(
+                app,
+                DictationPhase::Error,
+                Some("Failed to lock settings".to_string()),
+            );
+            return;
+        }
+    };
+
+    play_audio_cue(&settings, AudioCue::Stop);
+
+    if settings.silence_threshold > 0.0 {
+        match recording_rms(&audio_path) {
+            Ok(rms) if rms < settings.silence_threshold => {
+                let _ = fs::remove_file(&audio_path);
+                let _ = set_phase(state, RuntimePhase::Idle);
+                emit_status(
+                    app,
+                    DictationPhase::Idle,
+                    Some("No speech detected".to_string()),
+                );
+                return;
+            }
+            Ok(_) => {}
+            Err(err) => eprintln!("skipping silence check: {err}"),
+        }
+    }
+
+    if settings.trim_threshold > 0.0 {
+        if let Err(err) = trim_recording_silence(
+            &audio_path,
+            settings.trim_threshold,
+            settings.trim_margin_ms,
+        ) {
+            eprintln!("skipping silence trim: {err}");
+        }
+    }
+
+    let _ = set_phase(state, RuntimePhase::Transcribing);
+    if !is_runtime_ready(state).unwrap_or(false) {
+        emit_status(
+            app,
+            DictationPhase::Transcribing,
+            Some("Waiting for ASR setup to finish...".to_string()),
+        );
+    }
+
+    match wait_for_runtime_ready(state) {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = fs::remove_file(&audio_path);
+            let _ = set_phase(state, RuntimePhase::Idle);
+            emit_status(
+                app,
+                DictationPhase::Error,
+                Some("ASR setup failed, so the recording could not be transcribed".to_string()),
+            );
+            return;
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&audio_path);
+            let _ = set_phase(state, RuntimePhase::Idle);
+            emit_status(app, DictationPhase::Error, Some(err));
+            return;
+        }
+    }
+
+    emit_status(
+        app,
+        DictationPhase::Transcribing,
+        Some("Transcribing speech...".to_string()),
+    );
+
+    let mut completed = false;
+    let mut kept_metadata = None;
+    let progress_ticker = (settings.progress_interval_ms > 0).then(|| {
+        spawn_progress_ticker(
+            app,
+            state,
+            Duration::from_millis(settings.progress_interval_ms),
+        )
+    });
+    let started_at = Instant::now();
+    let transcript = transcribe_long_audio(&settings, app, state, &audio_path, false);
+    let transcription_elapsed = started_at.elapsed();
+    drop(progress_ticker);
+    if let Ok(transcription) = &transcript {
+        record_timing(state, TimingKind::Transcription, transcription_elapsed);
+
+        if let Some(duration_ms) = transcription.duration_ms {
+            record_timing(
+                state,
+                TimingKind::Inference,
+                Duration::from_millis(duration_ms),
+            );
+        }
+    }
+
+    match transcript {
+        Ok(Transcription { text, language, .. }) => {
+            let text = if settings.remove_fillers {
+                strip_filler_words(&text, &settings.filler_words)
+            } else {
+                text
+            };
+            let text = if settings.apply_voice_commands {
+                replace_voice_commands(&text, &settings.voice_commands)
+            } else {
+                text
+            };
+            let text = match settings.post_process_command.as_deref() {
+                Some(command_line) => {
+                    post_process_transcript(command_line, &text).unwrap_or_else(|err| {
+                        eprintln!("using the unprocessed transcript: {err}");
+                        text
+                    })
+                }
+                None => text,
+            };
+
+            // Logged before the event so an open history window refreshes
+            // with the new entry.
+            if settings.save_history {
+                if let Err(err) = append_history(app, &settings, &text) {
+                    eprintln!("{err}");
+                }
+            }
+
+            let _ = app.emit(TRANSCRIPT_EVENT, text.clone());
+
+            if settings.keep_recordings {
+                let sample_rate = WavReader::open(&audio_path)
+                    .map(|reader| reader.spec().sample_rate)
+                    .unwrap_or(0);
+                kept_metadata = Some(RecordingMetadata {
+                    model: settings.model.as_hf_id().to_string(),
+                    language: settings.language.clone(),
+                    detected_language: language.clone(),
+                    duration_ms: recording_elapsed.as_millis() as u64,
+                    sample_rate,
+                    transcript: text.clone(),
+                });
+            }
+
+            if let Some(language) = &language {
+                let _ = app.emit(DETECTED_LANGUAGE_EVENT, language.clone());
+                track_detected_language(app, state, &settings, language);
+            }
+
+            if let Some(overlay) = app.get_webview_window(OVERLAY_LABEL) {
+                let _ = overlay.hide();
+            }
+
+            // The template and suffix only affect what gets delivered;
+            // history and the transcript events keep the text as spoken.
+            let delivered_text = format!(
+                "{}{}",
+                apply_paste_template(settings.paste_template.as_deref(), &text),
+                unescape_suffix(&settings.append_suffix)
+            );
+
+            // Held transcripts report their result once confirmed.
+            let delivered = match settings.output_mode {
+                OutputMode::ConfirmBeforeInject => {
+                    hold_for_review(app, state, &delivered_text, target.clone())
+                }
+                OutputMode::ClipboardOnly => {
+                    let delivered = copy_transcript_to_clipboard(&delivered_text);
+                    emit_injection_result(app, DeliveryMethod::Clipboard, None, &delivered);
+                    delivered
+                }
+                OutputMode::InjectAtCursor | OutputMode::Both => {
+                    let foreground = FocusTarget::capture();
+                    let (method, delivered) =
+                        inject_unless_blocked(&settings, &target, &foreground, &delivered_text);
+                    emit_injection_result(app, method, foreground.app, &delivered);
+                    delivered
+                }
+            };
+
+            match delivered {
+                Ok(()) => completed = true,
+                Err(err) => emit_status(app, DictationPhase::Error, Some(err)),
+            }
+
+            let summary = DictationSummary {
+                word_count: text.split_whitespace().count(),
+                transcript: text,
+                language,
+                recording_ms: recording_elapsed.as_millis() as u64,
+                transcription_ms: transcription_elapsed.as_millis() as u64,
+            };
+            let _ = app.emit(COMPLETE_EVENT, summary);
+        }
+        Err(err) if err.code == ErrorCode::Cancelled => {
+            emit_status(app, DictationPhase::Idle, Some(err.message));
+        }
+        Err(err) if err.message == NOTHING_HEARD && settings.quiet_empty_transcripts => {
+            emit_status(app, DictationPhase::Idle, Some(err.message));
+        }
+        Err(err) => {
+            emit_status(app, DictationPhase::Error, Some(err.message));
+        }
+    }
+
+    match kept_metadata {
+        Some(metadata) => {
+            if let Err(err) = keep_recording(app, &audio_path, &metadata) {
+                eprintln!("{err}");
+                let _ = fs::remove_file(&audio_path);
+            }
+        }
+        None => {
+            let _ = fs::remove_file(&audio_path);
+        }
+    }
+    let _ = set_phase(state, RuntimePhase::Idle);
+    if completed {
+        emit_status_with_linger(
+            app,
+            DictationPhase::Idle,
+            Some("Done".to_string()),
+            Duration::from_millis(settings.overlay_hide_delay_ms),
+        );
+    } else {
+        emit_status(app, DictationPhase::Idle, None);
+    }
+}
+
+fn worker_stream_failed(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    active: &mut Option<RecorderSession>,
+    path: &Path,
+    message: String,
+) {
+    // Errors from an earlier, already finished session are stale.
+    if active.as_ref().map(|session| session.path.as_path()) != Some(path) {
+        return;
+    }
+
+    if let Some(session) = active.take() {
+        if let Ok(path) = session.finalize() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    let _ = set_phase(state, RuntimePhase::Idle);
+    emit_status(
+        app,
+        DictationPhase::Error,
+        Some(format!("Microphone stopped working: {message}")),
+    );
+}
+
+fn run_worker_loop(app: AppHandle, state: Arc<AppRuntime>, rx: Receiver<WorkerCommand>) {
+    let mut active_session: Option<RecorderSession> = None;
+
+    // A panicking handler must not take the loop down with it: the sender
+    // would keep accepting commands that nobody ever handles.
+    while let Ok(command) = rx.recv() {
+        if let WorkerCommand::Shutdown { done } = command {
+            if let Some(session) = active_session.take() {
+                // Leave a valid WAV behind rather than a truncated one.
+                match session.finalize() {
+                    Ok(path) => {
+                        let _ = fs::remove_file(path);
+                    }
+                    Err(err) => eprintln!("{err}"),
+                }
+            }
+            let _ = done.send(());
+            return;
+        }
+
+        let handled = panic::catch_unwind(AssertUnwindSafe(|| {
+            handle_worker_command(&app, &state, &mut active_session, command)
+        }));
+        if let Err(payload) = handled {
+            recover_from_worker_panic(&app, &state, &mut active_session, payload.as_ref());
+        }
+    }
+}
+
+fn handle_worker_command(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    active: &mut Option<RecorderSession>,
+    command: WorkerCommand,
+) {
+    match command {
+        WorkerCommand::Start => worker_start(app, state, active),
+        WorkerCommand::Stop => {
+            worker_stop(app, state, active);
+            start_queued_dictation(app, state, active);
+        }
+        WorkerCommand::Toggle => {
+            if current_phase(state).ok() == Some(RuntimePhase::Listening) {
+                worker_stop(app, state, active);
+                start_queued_dictation(app, state, active);
+            } else {
+                worker_start(app, state, active);
+            }
+        }
+        WorkerCommand::StreamFailed { path, message } => {
+            worker_stream_failed(app, state, active, &path, message);
+        }
+        // Handled by `run_worker_loop` before dispatch.
+        WorkerCommand::Shutdown { done } => {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// Runs a start that was queued while the last transcription was running.
+fn start_queued_dictation(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    active: &mut Option<RecorderSession>,
+) {
+    if take_queued_start(state) && is_dictation_enabled(state).unwrap_or(false) {
+        worker_start(app, state, active);
+    }
+}
+
+/// Exits after stopping any transcription and letting the worker close an
+/// in-progress recording. Runs on its own thread so callers on the main
+/// thread never block.
+fn quit_app_internal(app: &AppHandle, state: &Arc<AppRuntime>) {
+    let app = app.clone();
+    let state = state.clone();
+    thread::spawn(move || {
+        // A running sidecar would keep the worker busy until it finished.
+        let _ = abort_transcription_internal(&state);
+
+        let (done_tx, done_rx) = mpsc::channel();
+        if state
+            .worker_tx
+            .send(WorkerCommand::Shutdown { done: done_tx })
+            .is_ok()
+            && done_rx.recv_timeout(SHUTDOWN_TIMEOUT).is_err()
+        {
+            eprintln!("worker did not shut down in time, exiting anyway");
+        }
+
+        app.exit(0);
+    });
+}
+
+/// Drops whatever the panicking command left behind and puts the runtime
+/// back to Idle so the next trigger starts cleanly.
+fn recover_from_worker_panic(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    active: &mut Option<RecorderSession>,
+    payload: &(dyn std::any::Any + Send),
+) {
+    let reason = payload
+        .downcast_ref::<&str>()
+        .map(|reason| reason.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    eprintln!("dictation worker panicked: {reason}");
+
+    *active = None;
+    let _ = abort_transcription_internal(state);
+    set_transcription_stage(state, None);
+    let _ = set_phase(state, RuntimePhase::Idle);
+    emit_status(
+        app,
+        DictationPhase::Error,
+        Some(format!(
+            "Dictation hit an internal error ({reason}). Please try again"
+        )),
+    );
+}
+
+fn is_dictation_enabled(state: &Arc<AppRuntime>) -> Result<bool, String> {
+    state
+        .enabled
+        .lock()
+        .map(|enabled| *enabled)
+        .map_err(|_| "Failed to lock dictation enabled state".to_string())
+}
+
+fn set_dictation_enabled_internal(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    enabled: bool,
+) -> Result<(), String> {
+    *state
+        .enabled
+        .lock()
+        .map_err(|_| "Failed to lock dictation enabled state".to_string())? = enabled;
+
+    if let Ok(item) = state.enabled_menu_item.lock() {
+        if let Some(item) = item.as_ref() {
+            let _ = item.set_checked(enabled);
+        }
+    }
+
+    if current_phase(state)? == RuntimePhase::Idle {
+        let message = if enabled {
+            "Dictation enabled"
+        } else {
+            "Dictation paused"
+        };
+        emit_status(app, DictationPhase::Idle, Some(message.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Returns `false` when a start/toggle arrives too soon after the previous one.
+fn accept_activation(state: &Arc<AppRuntime>) -> Result<bool, String> {
+    let mut last_activation = state
+        .last_activation
+        .lock()
+        .map_err(|_| "Failed to lock activation state".to_string())?;
+
+    let now = Instant::now();
+    if last_activation.is_some_and(|previous| now.duration_since(previous) < ACTIVATION_DEBOUNCE) {
+        return Ok(false);
+    }
+
+    *last_activation = Some(now);
+    Ok(true)
+}
+
+fn queue_command(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    command: WorkerCommand,
+) -> Result<(), String> {
+    // While paused the shortcut stays registered but can only finish a
+    // recording that was already in progress.
+    if !is_dictation_enabled(state)? {
+        let listening = current_phase(state).ok() == Some(RuntimePhase::Listening);
+        match command {
+            WorkerCommand::Start => {
+                emit_blocked(app, state, "Dictation is paused");
+                return Ok(());
+            }
+            WorkerCommand::Toggle if !listening => {
+                emit_blocked(app, state, "Dictation is paused");
+                return Ok(());
+            }
+            WorkerCommand::Toggle
+            | WorkerCommand::Stop
+            | WorkerCommand::StreamFailed { .. }
+            | WorkerCommand::Shutdown { .. } => {}
+        }
+    }
+
+    if current_phase(state).ok() == Some(RuntimePhase::Transcribing) {
+        let queue_next = state
+            .settings
+            .lock()
+            .map_err(|_| "Failed to lock settings".to_string())?
+            .queue_next_dictation;
+        match command {
+            WorkerCommand::Start | WorkerCommand::Toggle if queue_next => {
+                if accept_activation(state)? {
+                    // A second toggle takes the queued start back.
+                    let queued =
+                        matches!(command, WorkerCommand::Start) || !take_queued_start(state);
+                    set_queued_start(state, queued)?;
+                    if queued {
+                        emit_status(
+                            app,
+                            DictationPhase::Transcribing,
+                            Some("Next dictation queued".to_string()),
+                        );
+                    }
+                }
+                return Ok(());
+            }
+            WorkerCommand::Start | WorkerCommand::Toggle => {
+                emit_blocked(app, state, "Busy transcribing");
+                return Ok(());
+            }
+            // Releasing a hold key before the transcription ends means the
+            // queued recording is no longer wanted.
+            WorkerCommand::Stop => {
+                take_queued_start(state);
+                return Ok(());
+            }
+            WorkerCommand::StreamFailed { .. } | WorkerCommand::Shutdown { .. } => {}
+        }
+    }
+
+    let is_activation = matches!(command, WorkerCommand::Start | WorkerCommand::Toggle);
+    if is_activation && !accept_activation(state)? {
+        return Ok(());
+    }
+
+    state
+        .worker_tx
+        .send(command)
+        .map_err(|err| format!("Failed to send worker command: {err}"))
+}
+
+fn set_queued_start(state: &Arc<AppRuntime>, queued: bool) -> Result<(), String> {
+    *state
+        .queued_start
+        .lock()
+        .map_err(|_| "Failed to lock queued start".to_string())? = queued;
+    Ok(())
+}
+
+/// Clears the queued start, returning whether one was pending.
+fn take_queued_start(state: &Arc<AppRuntime>) -> bool {
+    state
+        .queued_start
+        .lock()
+        .map(|mut queued| std::mem::take(&mut *queued))
+        .unwrap_or(false)
+}
+
+fn start_dictation_internal(app: &AppHandle, state: &Arc<AppRuntime>) -> Result<(), String> {
+    queue_command(app, state, WorkerCommand::Start)
+}
+
+fn stop_dictation_internal(app: &AppHandle, state: &Arc<AppRuntime>) -> Result<(), String> {
+    queue_command(app, state, WorkerCommand::Stop)
+}
+
+fn toggle_dictation_internal(app: &AppHandle, state: &Arc<AppRuntime>) -> Result<(), String> {
+    queue_command(app, state, WorkerCommand::Toggle)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModifierKey {
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+}
+
+impl ModifierKey {
+    fn parse(token: &str) -> Option<Self> {
+        match token.trim().to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Some(Self::Ctrl),
+            "shift" => Some(Self::Shift),
+            "alt" | "option" => Some(Self::Alt),
+            "meta" | "super" | "cmd" | "command" | "win" | "windows" => Some(Self::Super),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Ctrl => "Ctrl",
+            Self::Shift => "Shift",
+            Self::Alt => "Alt",
+            Self::Super => "Super",
+        }
+    }
+}
+
+/// A trigger made of a lone modifier key, optionally double-tapped. The
+/// global-shortcut plugin cannot register these, so they are polled instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ModifierTrigger {
+    key: ModifierKey,
+    double_tap: bool,
+}
+
+impl ModifierTrigger {
+    fn parse(shortcut_text: &str) -> Option<Self> {
+        let tokens: Vec<&str> = shortcut_text
+            .split('+')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        match tokens.as_slice() {
+            [key] => ModifierKey::parse(key).map(|key| Self {
+                key,
+                double_tap: false,
+            }),
+            [prefix, key] if prefix.eq_ignore_ascii_case("double") => {
+                ModifierKey::parse(key).map(|key| Self {
+                    key,
+                    double_tap: true,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn to_shortcut_text(self) -> String {
+        if self.double_tap {
+            format!("Double+{}", self.key.label())
+        } else {
+            self.key.label().to_string()
+        }
+    }
+}
+
+#[cfg(windows)]
+fn modifier_pressed(key: ModifierKey) -> Option<bool> {
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetAsyncKeyState(virtual_key: i32) -> i16;
+    }
+
+    const VK_SHIFT: i32 = 0x10;
+    const VK_CONTROL: i32 = 0x11;
+    const VK_MENU: i32 = 0x12;
+    const VK_LWIN: i32 = 0x5B;
+    const VK_RWIN: i32 = 0x5C;
+
+    let is_down = |virtual_key: i32| {
+        // SAFETY: GetAsyncKeyState only reads global keyboard state.
+        unsafe { GetAsyncKeyState(virtual_key) < 0 }
+    };
+
+    Some(match key {
+        ModifierKey::Ctrl => is_down(VK_CONTROL),
+        ModifierKey::Shift => is_down(VK_SHIFT),
+        ModifierKey::Alt => is_down(VK_MENU),
+        ModifierKey::Super => is_down(VK_LWIN) || is_down(VK_RWIN),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn modifier_pressed(key: ModifierKey) -> Option<bool> {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceFlagsState(state_id: i32) -> u64;
+    }
+
+    const HID_SYSTEM_STATE: i32 = 1;
+    const FLAG_SHIFT: u64 = 1 << 17;
+    const FLAG_CONTROL: u64 = 1 << 18;
+    const FLAG_ALTERNATE: u64 = 1 << 19;
+    const FLAG_COMMAND: u64 = 1 << 20;
+
+    // SAFETY: CGEventSourceFlagsState only reads global modifier state.
+    let flags = unsafe { CGEventSourceFlagsState(HID_SYSTEM_STATE) };
+    let mask = match key {
+        ModifierKey::Ctrl => FLAG_CONTROL,
+        ModifierKey::Shift => FLAG_SHIFT,
+        ModifierKey::Alt => FLAG_ALTERNATE,
+        ModifierKey::Super => FLAG_COMMAND,
+    };
+
+    Some(flags & mask != 0)
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn modifier_pressed(_key: ModifierKey) -> Option<bool> {
+    None
+}
+
+#[cfg(windows)]
+fn mouse_button_pressed(button: MouseButton) -> Option<bool> {
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetAsyncKeyState(virtual_key: i32) -> i16;
+    }
+
+    const VK_MBUTTON: i32 = 0x04;
+    const VK_XBUTTON1: i32 = 0x05;
+    const VK_XBUTTON2: i32 = 0x06;
+
+    let virtual_key = match button {
+        MouseButton::Middle => VK_MBUTTON,
+        MouseButton::Back => VK_XBUTTON1,
+        MouseButton::Forward => VK_XBUTTON2,
+    };
+
+    // SAFETY: GetAsyncKeyState only reads global input state.
+    Some(unsafe { GetAsyncKeyState(virtual_key) < 0 })
+}
+
+#[cfg(target_os = "macos")]
+fn mouse_button_pressed(button: MouseButton) -> Option<bool> {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceButtonState(state_id: i32, button: u32) -> bool;
+    }
+
+    const HID_SYSTEM_STATE: i32 = 1;
+
+    let button = match button {
+        MouseButton::Middle => 2,
+        MouseButton::Back => 3,
+        MouseButton::Forward => 4,
+    };
+
+    // SAFETY: CGEventSourceButtonState only reads global mouse state.
+    Some(unsafe { CGEventSourceButtonState(HID_SYSTEM_STATE, button) })
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn mouse_button_pressed(_button: MouseButton) -> Option<bool> {
+    None
+}
+
+/// Maps `Numpad0`, `Numpad Enter`, `KP_Add`-style tokens to the key codes
+/// the global-shortcut parser understands.
+fn normalize_numpad_token(token: &str) -> Option<String> {
+    let lower = token.to_ascii_lowercase();
+    let rest = ["numpad", "num", "kp"]
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))?;
+    // Only one separator is dropped, so `Numpad-` keeps its `-` key.
+    let rest = rest
+        .strip_prefix([' ', '_', '-'])
+        .filter(|name| !name.is_empty())
+        .unwrap_or(rest);
+
+    let name = match rest {
+        digit if digit.len() == 1 && digit.as_bytes()[0].is_ascii_digit() => digit,
+        "enter" | "return" => "Enter",
+        "add" | "plus" | "+" => "Add",
+        "subtract" | "minus" | "-" => "Subtract",
+        "multiply" | "*" => "Multiply",
+        "divide" | "/" => "Divide",
+        "decimal" | "." => "Decimal",
+        "equal" | "=" => "Equal",
+        _ => return None,
+    };
+
+    Some(format!("Numpad{name}"))
+}
+
+/// Maps punctuation characters and their names to the global-shortcut codes.
+/// Names are used because `+` separates shortcut parts.
+fn normalize_punctuation_token(token: &str) -> Option<&'static str> {
+    let name = match token.to_ascii_lowercase().as_str() {
+        "," | "comma" => "Comma",
+        "." | "period" => "Period",
+        ";" | "semicolon" => "Semicolon",
+        "'" | "quote" => "Quote",
+        "`" | "backquote" | "backtick" => "Backquote",
+        "[" | "bracketleft" => "BracketLeft",
+        "]" | "bracketright" => "BracketRight",
+        "\\" | "backslash" => "Backslash",
+        "/" | "slash" => "Slash",
+        "-" | "minus" => "Minus",
+        "=" | "equal" => "Equal",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+fn normalize_shortcut_key_token(token: &str) -> Result<String, String> {
+    let trimmed = token.trim();
+    if trimmed.is_empty() {
+        return Err("Shortcut key cannot be empty".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("space") || trimmed == " " {
+        return Ok("Space".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("esc") || trimmed.eq_ignore_ascii_case("escape") {
+        return Ok("Escape".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("enter") {
+        return Ok("Enter".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("tab") {
+        return Ok("Tab".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("backspace") {
+        return Ok("Backspace".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("delete") {
+        return Ok("Delete".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("up") || trimmed.eq_ignore_ascii_case("arrowup") {
+        return Ok("ArrowUp".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("down") || trimmed.eq_ignore_ascii_case("arrowdown") {
+        return Ok("ArrowDown".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("left") || trimmed.eq_ignore_ascii_case("arrowleft") {
+        return Ok("ArrowLeft".to_string());
+    }
+
+    if trimmed.eq_ignore_ascii_case("right") || trimmed.eq_ignore_ascii_case("arrowright") {
+        return Ok("ArrowRight".to_string());
+    }
+
+    if let Some(name) = normalize_numpad_token(trimmed) {
+        return Ok(name);
+    }
+
+    if let Some(name) = normalize_punctuation_token(trimmed) {
+        return Ok(name.to_string());
+    }
+
+    if trimmed.len() == 1 {
+        let ch = trimmed.chars().next().unwrap_or_default();
+        if ch.is_ascii_alphabetic() {
+            return Ok(ch.to_ascii_uppercase().to_string());
+        }
+
+        if ch.is_ascii_digit() {
+            return Ok(ch.to_string());
+        }
+    }
+
+    let upper = trimmed.to_ascii_uppercase();
+    if upper.starts_with('F')
+        && upper.len() <= 3
+        && upper
+            .chars()
+            .skip(1)
+            .all(|character| character.is_ascii_digit())
+    {
+        return Ok(upper);
+    }
+
+    Ok(trimmed.to_string())
+}
+
+fn normalize_shortcut_text(shortcut_text: &str) -> Result<String, String> {
+    if let Some(trigger) = ModifierTrigger::parse(shortcut_text) {
+        return Ok(trigger.to_shortcut_text());
+    }
+
+    let parsed_direct: Result<Shortcut, _> = shortcut_text.trim().parse();
+    if let Ok(shortcut) = parsed_direct {
+        return Ok(shortcut.into_string());
+    }
+
+    let mut tokens: Vec<String> = shortcut_text
+        .split('+')
         .map(|token| token.trim())
         .filter(|token| !token.is_empty())
         .map(|token| token.to_string())
         .collect();
 
-    if tokens.is_empty() {
-        return Err("Shortcut cannot be empty".to_string());
+    if tokens.is_empty() {
+        return Err("Shortcut cannot be empty".to_string());
+    }
+
+    let key_token = tokens
+        .pop()
+        .ok_or_else(|| "Shortcut key cannot be empty".to_string())?;
+
+    let mut modifiers = Vec::new();
+    for token in tokens {
+        let normalized_modifier = ModifierKey::parse(&token)
+            .map(ModifierKey::label)
+            .ok_or_else(|| {
+                format!("Unsupported modifier '{token}'. Use Ctrl, Shift, Alt, or Super.")
+            })?;
+
+        if !modifiers
+            .iter()
+            .any(|existing: &String| existing == normalized_modifier)
+        {
+            modifiers.push(normalized_modifier.to_string());
+        }
+    }
+
+    let key = normalize_shortcut_key_token(&key_token)?;
+    let normalized = if modifiers.is_empty() {
+        key
+    } else {
+        format!("{}+{key}", modifiers.join("+"))
+    };
+
+    normalized
+        .parse::<Shortcut>()
+        .map(|shortcut| shortcut.into_string())
+        .map_err(|error| {
+            format!(
+                "Invalid shortcut '{shortcut_text}'. Try keys like F8, Space, Ctrl+Shift+Space: {error}"
+            )
+        })
+}
+
+fn handle_trigger_event(app: &AppHandle, state: &Arc<AppRuntime>, event_state: ShortcutState) {
+    let settings = match state.settings.lock() {
+        Ok(settings) => settings.clone(),
+        Err(_) => return,
+    };
+
+    match settings.recording_mode {
+        RecordingMode::Hold => {
+            if event_state == ShortcutState::Pressed {
+                let _ = start_dictation_internal(app, state);
+            }
+
+            if event_state == ShortcutState::Released {
+                let _ = stop_dictation_internal(app, state);
+            }
+        }
+        RecordingMode::Toggle => {
+            if event_state == ShortcutState::Pressed {
+                let _ = toggle_dictation_internal(app, state);
+            }
+        }
+    }
+}
+
+fn next_modifier_watch_generation(state: &Arc<AppRuntime>) -> Result<u64, String> {
+    let mut generation = state
+        .modifier_watch_generation
+        .lock()
+        .map_err(|_| "Failed to lock modifier watcher state".to_string())?;
+    *generation += 1;
+    Ok(*generation)
+}
+
+fn spawn_modifier_watcher(
+    app: AppHandle,
+    state: Arc<AppRuntime>,
+    trigger: ModifierTrigger,
+    generation: u64,
+) {
+    thread::spawn(move || {
+        let mut was_down = false;
+        let mut armed = false;
+        let mut last_tap_released: Option<Instant> = None;
+
+        loop {
+            let current = state
+                .modifier_watch_generation
+                .lock()
+                .map(|current| *current)
+                .unwrap_or_default();
+            if current != generation {
+                return;
+            }
+
+            let is_down = modifier_pressed(trigger.key).unwrap_or(false);
+
+            if is_down && !was_down {
+                // A double-tap trigger only fires on the second press that
+                // follows a quick first tap.
+                armed = !trigger.double_tap
+                    || last_tap_released
+                        .is_some_and(|released| released.elapsed() <= DOUBLE_TAP_WINDOW);
+                if armed {
+                    handle_trigger_event(&app, &state, ShortcutState::Pressed);
+                }
+            }
+
+            if !is_down && was_down {
+                if armed {
+                    handle_trigger_event(&app, &state, ShortcutState::Released);
+                    last_tap_released = None;
+                } else {
+                    last_tap_released = Some(Instant::now());
+                }
+                armed = false;
+            }
+
+            was_down = is_down;
+            thread::sleep(MODIFIER_POLL_INTERVAL);
+        }
+    });
+}
+
+/// Polls `button` like a modifier trigger. The click still reaches the app
+/// under the cursor, since polling cannot swallow it.
+fn spawn_mouse_watcher(
+    app: AppHandle,
+    state: Arc<AppRuntime>,
+    button: MouseButton,
+    generation: u64,
+) {
+    thread::spawn(move || {
+        let mut was_down = false;
+
+        loop {
+            let current = state
+                .mouse_watch_generation
+                .lock()
+                .map(|current| *current)
+                .unwrap_or_default();
+            if current != generation {
+                return;
+            }
+
+            let is_down = mouse_button_pressed(button).unwrap_or(false);
+            if is_down && !was_down {
+                handle_trigger_event(&app, &state, ShortcutState::Pressed);
+            }
+            if !is_down && was_down {
+                handle_trigger_event(&app, &state, ShortcutState::Released);
+            }
+
+            was_down = is_down;
+            thread::sleep(MODIFIER_POLL_INTERVAL);
+        }
+    });
+}
+
+/// Stops any previous mouse watcher and starts one for `button`.
+fn register_mouse_trigger(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    button: Option<MouseButton>,
+) -> Result<(), String> {
+    if let Some(button) = button {
+        if mouse_button_pressed(button).is_none() {
+            return Err(
+                "Mouse button triggers are only supported on Windows and macOS".to_string(),
+            );
+        }
+    }
+
+    let generation = {
+        let mut generation = state
+            .mouse_watch_generation
+            .lock()
+            .map_err(|_| "Failed to lock mouse watcher state".to_string())?;
+        *generation += 1;
+        *generation
+    };
+
+    if let Some(button) = button {
+        spawn_mouse_watcher(app.clone(), state.clone(), button, generation);
+    }
+
+    Ok(())
+}
+
+/// Reply line from the sidecar in `--wake-word` mode.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct WakeReply {
+    ready: bool,
+    wake: bool,
+}
+
+/// Long-running sidecar that keeps the small ASR model loaded and checks
+/// short windows for the wake word. Killed on drop.
+struct WakeDetector {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    window_path: PathBuf,
+}
+
+impl WakeDetector {
+    fn spawn(settings: &AppSettings, app: &AppHandle, wake_word: &str) -> Result<Self, String> {
+        let mut command = sidecar_command(settings, app)?;
+        command
+            .arg("--wake-word")
+            .arg(wake_word)
+            .arg("--model")
+            .arg(WAKE_MODEL.as_hf_id())
+            .arg("--language")
+            .arg(&settings.language)
+            .arg("--device")
+            .arg(settings.compute_device.as_arg())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+
+        let mut child = command
+            .spawn()
+            .map_err(|err| format!("Failed to launch wake word detector: {err}"))?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err("Wake word detector has no stdio".to_string());
+        };
+
+        let mut detector = Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            window_path: std::env::temp_dir()
+                .join(format!("delulu-wake-{}.wav", std::process::id())),
+        };
+        if !detector.read_reply()?.ready {
+            return Err("Wake word detector failed to load its model".to_string());
+        }
+
+        Ok(detector)
+    }
+
+    fn read_reply(&mut self) -> Result<WakeReply, String> {
+        let mut line = String::new();
+        let read = self
+            .stdout
+            .read_line(&mut line)
+            .map_err(|err| format!("Failed to read wake word detector: {err}"))?;
+        if read == 0 {
+            return Err("Wake word detector exited".to_string());
+        }
+
+        serde_json::from_str(line.trim())
+            .map_err(|err| format!("Unexpected wake word detector output: {err}"))
+    }
+
+    /// Whether the wake word was said in `samples` (mono).
+    fn heard(&mut self, samples: &[f32], sample_rate: u32) -> Result<bool, String> {
+        write_recording_chunk(
+            &self.window_path,
+            RecordingFormat::Pcm16.wav_spec(1, sample_rate),
+            samples,
+        )?;
+        writeln!(self.stdin, "{}", self.window_path.display())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|err| format!("Failed to reach wake word detector: {err}"))?;
+
+        Ok(self.read_reply()?.wake)
+    }
+}
+
+impl Drop for WakeDetector {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.window_path);
+    }
+}
+
+fn build_wake_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    tx: SyncSender<Vec<f32>>,
+) -> Result<Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                let mono = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        frame
+                            .iter()
+                            .map(|&sample| f32::from_sample(sample))
+                            .sum::<f32>()
+                            / frame.len() as f32
+                    })
+                    .collect();
+                // Dropped rather than blocking the audio thread when the
+                // listener falls behind.
+                let _ = tx.try_send(mono);
+            },
+            |err| eprintln!("wake word stream error: {err}"),
+            None,
+        )
+        .map_err(|err| format!("Failed to build wake word stream: {err}"))
+}
+
+fn samples_rms<'a>(samples: impl ExactSizeIterator<Item = &'a f32>) -> f32 {
+    let count = samples.len();
+    if count == 0 {
+        return 0.0;
+    }
+
+    let sum_squares: f32 = samples.map(|sample| sample * sample).sum();
+    (sum_squares / count as f32).sqrt()
+}
+
+fn is_current_wake_generation(state: &Arc<AppRuntime>, generation: u64) -> bool {
+    state
+        .wake_watch_generation
+        .lock()
+        .is_ok_and(|current| *current == generation)
+}
+
+/// A dictation the wake word started, which ends itself after a pause.
+struct WakeDictation {
+    started_at: Instant,
+    last_voice: Option<Instant>,
+}
+
+/// Listens for the wake word while idle and, once it is heard, records
+/// until the speaker pauses. Windows quieter than the silence threshold
+/// never reach the model, so a quiet room costs no inference.
+fn run_wake_listener(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    settings: &AppSettings,
+    wake_word: &str,
+    generation: u64,
+) -> Result<(), String> {
+    // The detector runs on the same Python runtime as transcription.
+    while !is_runtime_ready(state)? {
+        if !is_current_wake_generation(state, generation) {
+            return Ok(());
+        }
+        thread::sleep(WAKE_READY_POLL_INTERVAL);
+    }
+
+    // Loading the model takes a while; capture only starts once it is up.
+    let mut detector = WakeDetector::spawn(settings, app, wake_word)?;
+
+    let device = resolve_input_device(settings)?;
+    let supported = resolve_input_config(&device)?;
+    let sample_rate = supported.sample_rate().0;
+    let config: StreamConfig = supported.clone().into();
+    let (tx, rx) = mpsc::sync_channel(WAKE_QUEUE_CAPACITY);
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_wake_stream::<f32>(&device, &config, tx)?,
+        SampleFormat::I16 => build_wake_stream::<i16>(&device, &config, tx)?,
+        SampleFormat::U16 => build_wake_stream::<u16>(&device, &config, tx)?,
+        other => return Err(format!("Unsupported sample format: {other:?}")),
+    };
+    stream
+        .play()
+        .map_err(|err| format!("Failed to start wake word capture: {err}"))?;
+
+    let threshold = settings.silence_threshold.max(WAKE_MIN_LEVEL);
+    let window_len = (sample_rate as f32 * WAKE_WINDOW.as_secs_f32()) as usize;
+    let samples_in = |duration: Duration| (sample_rate as f32 * duration.as_secs_f32()) as usize;
+    let mut hop_len = samples_in(WAKE_HOP);
+    let mut window = VecDeque::with_capacity(window_len);
+    let mut since_check = 0;
+    let mut dictation: Option<WakeDictation> = None;
+    let mut last_audio = Instant::now();
+
+    while is_current_wake_generation(state, generation) {
+        let samples = match rx.recv_timeout(WAKE_READY_POLL_INTERVAL) {
+            Ok(samples) => samples,
+            Err(_) if last_audio.elapsed() < WAKE_STALL_TIMEOUT => continue,
+            Err(_) => return Err("The input device stopped delivering audio".to_string()),
+        };
+        last_audio = Instant::now();
+        let phase = current_phase(state)?;
+
+        if let Some(active) = &mut dictation {
+            match phase {
+                RuntimePhase::Listening => {
+                    if samples_rms(samples.iter()) >= threshold {
+                        active.last_voice = Some(Instant::now());
+                    }
+                    let ended = match active.last_voice {
+                        Some(voice) => voice.elapsed() >= WAKE_END_SILENCE,
+                        None => active.started_at.elapsed() >= WAKE_NO_SPEECH_TIMEOUT,
+                    };
+                    if ended {
+                        dictation = None;
+                        stop_dictation_internal(app, state)?;
+                    }
+                }
+                // The worker has not picked up the start yet.
+                RuntimePhase::Idle if active.started_at.elapsed() < WAKE_START_GRACE => {}
+                _ => dictation = None,
+            }
+            continue;
+        }
+
+        if phase != RuntimePhase::Idle || !is_dictation_enabled(state)? {
+            window.clear();
+            since_check = 0;
+            continue;
+        }
+
+        since_check += samples.len();
+        window.extend(samples);
+        if since_check >= hop_len {
+            // Only the newest window is checked; audio that queued up during
+            // the previous check is already stale.
+            while let Ok(samples) = rx.try_recv() {
+                window.extend(samples);
+            }
+        }
+        let overflow = window.len().saturating_sub(window_len);
+        window.drain(..overflow);
+        if since_check < hop_len || window.len() < window_len {
+            continue;
+        }
+        since_check = 0;
+
+        if samples_rms(window.iter()) < threshold {
+            continue;
+        }
+        let started = Instant::now();
+        let heard = detector.heard(window.make_contiguous(), sample_rate)?;
+        // A check slower than real time would keep the CPU busy back to
+        // back, so the next one waits twice as long as this one took.
+        let took = started.elapsed();
+        hop_len = samples_in((took * 2).clamp(WAKE_HOP, WAKE_MAX_HOP));
+        if heard {
+            window.clear();
+            // Whatever queued up during the check predates the wake word.
+            while rx.try_recv().is_ok() {}
+            start_dictation_internal(app, state)?;
+            dictation = Some(WakeDictation {
+                started_at: Instant::now(),
+                last_voice: None,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Restarts the wake word listener for `settings`, stopping any previous
+/// one.
+fn register_wake_word(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    settings: &AppSettings,
+) -> Result<(), String> {
+    let generation = {
+        let mut generation = state
+            .wake_watch_generation
+            .lock()
+            .map_err(|_| "Failed to lock wake word state".to_string())?;
+        *generation += 1;
+        *generation
+    };
+
+    let Some(wake_word) = settings.wake_word.clone() else {
+        return Ok(());
+    };
+    let app = app.clone();
+    let state = state.clone();
+    let settings = settings.clone();
+    thread::spawn(move || {
+        if let Err(err) = run_wake_listener(&app, &state, &settings, &wake_word, generation) {
+            eprintln!("wake word listener stopped: {err}");
+            emit_status(
+                &app,
+                DictationPhase::Error,
+                Some(format!("Wake word listening stopped: {err}")),
+            );
+        }
+    });
+
+    Ok(())
+}
+
+fn register_shortcut(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    shortcut_text: &str,
+) -> Result<String, AppError> {
+    let normalized_shortcut = normalize_shortcut_text(shortcut_text)?;
+    let modifier_trigger = ModifierTrigger::parse(&normalized_shortcut);
+
+    if let Some(trigger) = modifier_trigger {
+        if modifier_pressed(trigger.key).is_none() {
+            return Err(format!(
+                "Modifier-only shortcuts like '{normalized_shortcut}' are only supported on Windows and macOS"
+            )
+            .into());
+        }
+    }
+
+    if modifier_trigger.is_none() {
+        let shortcut: Shortcut = normalized_shortcut
+            .parse()
+            .map_err(|err| format!("Invalid shortcut '{normalized_shortcut}': {err}"))?;
+        if let Some(owner) = reserved_shortcut_owner(&shortcut) {
+            return Err(AppError::new(
+                ErrorCode::ShortcutInUse,
+                format!(
+                    "Shortcut '{normalized_shortcut}' is already in use by {owner}. Pick a different combination"
+                ),
+            ));
+        }
+    }
+
+    app.global_shortcut()
+        .unregister_all()
+        .map_err(|err| format!("Failed to clear previous shortcuts: {err}"))?;
+
+    if let Err(err) = bind_shortcut(app, state, &normalized_shortcut) {
+        // Keep dictation reachable through the old shortcut.
+        let previous = state
+            .registered_shortcut
+            .lock()
+            .map(|previous| previous.clone())
+            .unwrap_or_default();
+        if !previous.is_empty() && previous != normalized_shortcut {
+            if let Err(restore_err) = bind_shortcut(app, state, &previous) {
+                eprintln!("failed to restore shortcut '{previous}': {restore_err}");
+            }
+        }
+        return Err(err);
+    }
+
+    *state
+        .registered_shortcut
+        .lock()
+        .map_err(|_| "Failed to lock shortcut state".to_string())? = normalized_shortcut.clone();
+
+    Ok(normalized_shortcut)
+}
+
+/// Starts listening for an already normalized shortcut, replacing any
+/// modifier watcher.
+fn bind_shortcut(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    normalized: &str,
+) -> Result<(), AppError> {
+    let generation = next_modifier_watch_generation(state)?;
+
+    if let Some(trigger) = ModifierTrigger::parse(normalized) {
+        spawn_modifier_watcher(app.clone(), state.clone(), trigger, generation);
+        return Ok(());
+    }
+
+    let shortcut: Shortcut = normalized
+        .parse()
+        .map_err(|err| format!("Invalid shortcut '{normalized}': {err}"))?;
+
+    let state_for_handler = state.clone();
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            handle_trigger_event(app_handle, &state_for_handler, event.state);
+        })
+        .map_err(|err| match err {
+            ShortcutError::GlobalHotkey(detail)
+                if HOTKEY_TAKEN_ERRORS
+                    .iter()
+                    .any(|prefix| detail.starts_with(prefix)) =>
+            {
+                AppError::new(
+                    ErrorCode::ShortcutInUse,
+                    format!(
+                        "Shortcut '{normalized}' is already in use by the OS or another app. Pick a different combination"
+                    ),
+                )
+            }
+            err => format!("Failed to register shortcut handler: {err}").into(),
+        })
+}
+
+/// Combos the OS keeps for itself. Registering them can succeed while the
+/// shortcut never fires, so they are rejected up front.
+#[cfg(windows)]
+const RESERVED_SHORTCUTS: &[(&str, &str)] = &[
+    ("Super+Space", "Windows input language switching"),
+    ("Shift+Super+Space", "Windows input language switching"),
+    ("Super+L", "the Windows lock screen"),
+    ("Super+D", "the Windows desktop toggle"),
+    ("Super+Tab", "Windows Task View"),
+    ("Alt+Tab", "Windows app switching"),
+];
+
+#[cfg(target_os = "macos")]
+const RESERVED_SHORTCUTS: &[(&str, &str)] = &[
+    ("Super+Space", "Spotlight"),
+    ("Control+Space", "macOS input source switching"),
+    ("Alt+Super+Space", "Finder search"),
+    ("Super+Tab", "macOS app switching"),
+];
+
+#[cfg(not(any(windows, target_os = "macos")))]
+const RESERVED_SHORTCUTS: &[(&str, &str)] = &[];
+
+fn reserved_shortcut_owner(shortcut: &Shortcut) -> Option<&'static str> {
+    RESERVED_SHORTCUTS
+        .iter()
+        .find(|(reserved, _)| reserved.parse::<Shortcut>().ok().as_ref() == Some(shortcut))
+        .map(|(_, owner)| *owner)
+}
+
+fn switch_profile_internal(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    name: &str,
+) -> Result<AppSettings, AppError> {
+    let mut settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+
+    let index = settings
+        .profiles
+        .iter()
+        .position(|profile| profile.name == name)
+        .ok_or_else(|| format!("Profile '{name}' not found"))?;
+
+    let normalized_shortcut = register_shortcut(app, state, &settings.profiles[index].shortcut)?;
+    settings.profiles[index].shortcut = normalized_shortcut.clone();
+    settings.shortcut = normalized_shortcut;
+    settings.recording_mode = settings.profiles[index].recording_mode;
+    settings.active_profile = Some(index);
+    save_settings(app, &settings)?;
+
+    *state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())? = settings.clone();
+
+    Ok(settings)
+}
+
+/// Counts consecutive matching detections while `language` is "auto". When
+/// the streak reaches `language_pin_after` it suggests pinning, or pins
+/// right away with `auto_pin_language`; either happens once per streak.
+fn track_detected_language(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    settings: &AppSettings,
+    language: &str,
+) {
+    if !settings.language.eq_ignore_ascii_case("auto") || settings.language_pin_after == 0 {
+        return;
     }
 
-    let key_token = tokens
-        .pop()
-        .ok_or_else(|| "Shortcut key cannot be empty".to_string())?;
+    let detections = match state.language_streak.lock() {
+        Ok(mut streak) => {
+            let detections = match streak.as_ref() {
+                Some((previous, count)) if previous.eq_ignore_ascii_case(language) => count + 1,
+                _ => 1,
+            };
+            *streak = Some((language.to_string(), detections));
+            detections
+        }
+        Err(_) => {
+            eprintln!("Failed to lock language streak");
+            return;
+        }
+    };
+    if detections != settings.language_pin_after {
+        return;
+    }
 
-    let mut modifiers = Vec::new();
-    for token in tokens {
-        let normalized_modifier = match token.to_ascii_lowercase().as_str() {
-            "ctrl" | "control" => "Ctrl",
-            "shift" => "Shift",
-            "alt" | "option" => "Alt",
-            "meta" | "super" | "cmd" | "command" | "win" | "windows" => "Super",
-            _ => {
-                return Err(format!(
-                    "Unsupported modifier '{token}'. Use Ctrl, Shift, Alt, or Super."
-                ));
+    let pinned = settings.auto_pin_language
+        && match pin_language_internal(app, state, language) {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!("{err}");
+                false
             }
         };
+    let _ = app.emit(
+        LANGUAGE_SUGGESTION_EVENT,
+        LanguageSuggestion {
+            language: language.to_string(),
+            detections,
+            pinned,
+        },
+    );
+}
 
-        if !modifiers
-            .iter()
-            .any(|existing: &String| existing == normalized_modifier)
-        {
-            modifiers.push(normalized_modifier.to_string());
-        }
+/// Replaces "auto" with `language` for the current model. Each transcription
+/// passes the language to the sidecar, so no bootstrap is needed.
+fn pin_language_internal(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+    language: &str,
+) -> Result<AppSettings, String> {
+    let language = language.trim();
+    if language.is_empty() {
+        return Err("Language to pin is empty".to_string());
     }
 
-    let key = normalize_shortcut_key_token(&key_token)?;
-    let normalized = if modifiers.is_empty() {
-        key
+    let mut settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+    settings.language = language.to_string();
+    settings
+        .model_languages
+        .insert(settings.model, settings.language.clone());
+    save_settings(app, &settings)?;
+
+    *state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())? = settings.clone();
+    if let Ok(mut streak) = state.language_streak.lock() {
+        *streak = None;
+    }
+
+    Ok(settings)
+}
+
+fn reregister_current_shortcut(
+    app: &AppHandle,
+    state: &Arc<AppRuntime>,
+) -> Result<String, AppError> {
+    let shortcut = state
+        .registered_shortcut
+        .lock()
+        .map_err(|_| "Failed to lock shortcut state".to_string())?
+        .clone();
+
+    register_shortcut(app, state, &shortcut)
+}
+
+fn spawn_resume_watcher(app: AppHandle, state: Arc<AppRuntime>) {
+    thread::spawn(move || {
+        let mut last_tick = SystemTime::now();
+
+        loop {
+            thread::sleep(RESUME_CHECK_INTERVAL);
+
+            // A wall-clock gap far larger than the sleep interval means the
+            // machine was suspended, which can silently drop OS hotkey hooks.
+            let now = SystemTime::now();
+            let gap = now.duration_since(last_tick).unwrap_or_default();
+            last_tick = now;
+
+            if gap < RESUME_CHECK_INTERVAL + RESUME_GAP_THRESHOLD {
+                continue;
+            }
+
+            if let Err(err) = reregister_current_shortcut(&app, &state) {
+                eprintln!("failed to re-register shortcut after resume: {err}");
+            }
+        }
+    });
+}
+
+/// Polls the input device list and emits it whenever it changes, so an open
+/// settings window can refresh. Skipped while recording, when enumerating
+/// can stall some audio hosts.
+fn spawn_device_watcher(app: AppHandle, state: Arc<AppRuntime>) {
+    thread::spawn(move || {
+        let mut known = list_input_devices_internal().unwrap_or_default();
+
+        loop {
+            thread::sleep(DEVICE_POLL_INTERVAL);
+
+            if current_phase(&state).ok() != Some(RuntimePhase::Idle) {
+                continue;
+            }
+
+            let devices = match list_input_devices_internal() {
+                Ok(devices) => devices,
+                Err(err) => {
+                    eprintln!("failed to poll input devices: {err}");
+                    continue;
+                }
+            };
+            if devices != known {
+                let _ = app.emit(DEVICES_EVENT, devices.clone());
+                known = devices;
+            }
+        }
+    });
+}
+
+fn open_recordings_folder_internal(app: &AppHandle, state: &Arc<AppRuntime>) -> Result<(), String> {
+    let keep_recordings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .keep_recordings;
+    let resolved = state
+        .recordings_dir
+        .lock()
+        .map_err(|_| "Failed to lock recordings dir".to_string())?
+        .clone();
+    let dir = if keep_recordings {
+        kept_recordings_dir(app)
     } else {
-        format!("{}+{key}", modifiers.join("+"))
+        match resolved {
+            Some(dir) => dir,
+            None => resolve_recordings_dir(app)?,
+        }
     };
 
-    normalized
-        .parse::<Shortcut>()
-        .map(|shortcut| shortcut.into_string())
-        .map_err(|error| {
-            format!(
-                "Invalid shortcut '{shortcut_text}'. Try keys like F8, Space, Ctrl+Shift+Space: {error}"
-            )
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create recordings dir {}: {err}", dir.display()))?;
+
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|err| format!("Failed to open recordings folder: {err}"))
+}
+
+fn install_tray(app: &AppHandle, state: Arc<AppRuntime>) -> Result<(), String> {
+    let open_item = MenuItem::with_id(app, "open", "Open Settings", true, None::<&str>)
+        .map_err(|err| err.to_string())?;
+    let toggle_item =
+        MenuItem::with_id(app, "toggle", "Start / Stop Dictation", true, None::<&str>)
+            .map_err(|err| err.to_string())?;
+    let enabled_item = CheckMenuItem::with_id(
+        app,
+        "enabled",
+        "Dictation Enabled",
+        true,
+        is_dictation_enabled(&state)?,
+        None::<&str>,
+    )
+    .map_err(|err| err.to_string())?;
+    let warmup_item = MenuItem::with_id(app, "warmup", "Warm Up Model", true, None::<&str>)
+        .map_err(|err| err.to_string())?;
+    let history_item = MenuItem::with_id(app, "history", "Transcript History", true, None::<&str>)
+        .map_err(|err| err.to_string())?;
+    let recordings_item = MenuItem::with_id(
+        app,
+        "recordings",
+        "Open Recordings Folder",
+        true,
+        None::<&str>,
+    )
+    .map_err(|err| err.to_string())?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)
+        .map_err(|err| err.to_string())?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &open_item,
+            &toggle_item,
+            &enabled_item,
+            &warmup_item,
+            &history_item,
+            &recordings_item,
+            &quit_item,
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+
+    *state
+        .enabled_menu_item
+        .lock()
+        .map_err(|_| "Failed to lock tray menu state".to_string())? = Some(enabled_item);
+
+    let state_for_menu = state.clone();
+    let mut tray_builder = TrayIconBuilder::with_id(TRAY_ID);
+
+    if let Some(icon) = app.default_window_icon() {
+        tray_builder = tray_builder.icon(icon.clone());
+    }
+
+    tray_builder
+        .tooltip("Delulu Talks")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(move |app_handle, event| match event.id().as_ref() {
+            "open" => {
+                let _ = show_settings_window(app_handle);
+            }
+            "toggle" => {
+                let _ = toggle_dictation_internal(app_handle, &state_for_menu);
+            }
+            "enabled" => {
+                let enabled = is_dictation_enabled(&state_for_menu).unwrap_or(true);
+                let _ = set_dictation_enabled_internal(app_handle, &state_for_menu, !enabled);
+            }
+            "warmup" => {
+                let app_handle = app_handle.clone();
+                let state = state_for_menu.clone();
+                thread::spawn(move || {
+                    if let Err(err) = warmup_internal(&app_handle, &state) {
+                        eprintln!("model warmup failed: {err}");
+                    }
+                });
+            }
+            "history" => {
+                if let Err(err) = show_history_window(app_handle) {
+                    eprintln!("{err}");
+                }
+            }
+            "recordings" => {
+                if let Err(err) = open_recordings_folder_internal(app_handle, &state_for_menu) {
+                    eprintln!("{err}");
+                }
+            }
+            "quit" => quit_app_internal(app_handle, &state_for_menu),
+            _ => {}
         })
+        .build(app)
+        .map_err(|err| format!("Failed to create tray icon: {err}"))?;
+
+    Ok(())
 }
 
-fn register_shortcut(
+#[tauri::command]
+fn quit_app(app: AppHandle, state: State<'_, Arc<AppRuntime>>) {
+    quit_app_internal(&app, state.inner());
+}
+
+#[tauri::command]
+fn get_settings(state: State<'_, Arc<AppRuntime>>) -> Result<AppSettings, AppError> {
+    state
+        .settings
+        .lock()
+        .map(|settings| settings.clone())
+        .map_err(|_| "Failed to lock settings".to_string().into())
+}
+
+#[tauri::command]
+fn get_performance_stats(state: State<'_, Arc<AppRuntime>>) -> Result<PerformanceStats, AppError> {
+    let model = state
+        .settings
+        .lock()
+        .map(|settings| settings.model.as_hf_id().to_string())
+        .map_err(|_| "Failed to lock settings".to_string())?;
+
+    let history = state
+        .performance
+        .lock()
+        .map_err(|_| "Failed to lock performance history".to_string())?;
+
+    Ok(PerformanceStats {
+        model,
+        recorder_start: DurationStats::from_samples(&history.recorder_start),
+        transcription: DurationStats::from_samples(&history.transcription),
+        inference: DurationStats::from_samples(&history.inference),
+    })
+}
+
+#[tauri::command]
+fn open_recordings_folder(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+) -> Result<(), AppError> {
+    open_recordings_folder_internal(&app, state.inner()).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_recordings_usage(app: AppHandle) -> RecordingsUsage {
+    recordings_usage_internal(&app)
+}
+
+#[tauri::command]
+fn prune_recordings(
+    app: AppHandle,
+    keep_last: Option<usize>,
+    older_than_days: Option<u64>,
+) -> Result<usize, AppError> {
+    prune_recordings_internal(&app, keep_last, older_than_days).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_theme(app: AppHandle, state: State<'_, Arc<AppRuntime>>) -> Result<String, AppError> {
+    let preference = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .theme;
+    Ok(effective_theme(&app, preference).to_string())
+}
+
+#[tauri::command]
+fn get_history(app: AppHandle, limit: Option<usize>) -> Result<Vec<HistoryEntry>, AppError> {
+    read_history(&app, limit.unwrap_or(DEFAULT_HISTORY_LIMIT)).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn copy_history_entry(app: AppHandle, id: u64) -> Result<(), AppError> {
+    let entry = read_history(&app, usize::MAX)?
+        .into_iter()
+        .find(|entry| entry.timestamp_ms == id)
+        .ok_or_else(|| format!("History entry {id} not found"))?;
+
+    copy_transcript_to_clipboard(&entry.transcript).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn open_history(app: AppHandle) -> Result<(), AppError> {
+    show_history_window(&app).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_dictation_status(state: State<'_, Arc<AppRuntime>>) -> Result<RuntimeStatus, AppError> {
+    let ready = is_runtime_ready(state.inner())?;
+    let phase = match current_phase(state.inner())? {
+        RuntimePhase::Idle if !ready => DictationPhase::Bootstrapping,
+        RuntimePhase::Idle => DictationPhase::Idle,
+        RuntimePhase::Listening => DictationPhase::Listening,
+        RuntimePhase::Transcribing => DictationPhase::Transcribing,
+    };
+
+    Ok(RuntimeStatus { phase, ready })
+}
+
+#[tauri::command]
+async fn run_diagnostics(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+) -> Result<Vec<DiagnosticCheck>, AppError> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || run_diagnostics_internal(&app, &state))
+        .await
+        .map_err(|err| format!("Diagnostics task failed: {err}").into())
+}
+
+#[tauri::command]
+async fn query_environment(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+) -> Result<EnvironmentInfo, AppError> {
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+
+    tauri::async_runtime::spawn_blocking(move || query_environment_internal(&settings, &app))
+        .await
+        .map_err(|err| format!("Environment query task failed: {err}"))?
+        .map_err(AppError::from)
+}
+
+/// Runs a user-picked audio file through the sidecar off the main thread.
+async fn transcribe_file_internal(
+    app: AppHandle,
+    state: Arc<AppRuntime>,
+    path: String,
+    timestamps: bool,
+) -> Result<Transcription, AppError> {
+    let path = validate_audio_file(path)?;
+
+    if !is_runtime_ready(&state)? {
+        return Err(AppError::new(ErrorCode::NotReady, RUNTIME_NOT_READY));
+    }
+
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+    // Dictation shares the sidecar slot that `abort_transcription` kills.
+    let busy = BusyPhase::claim(
+        &app,
+        &state,
+        "Finish the current dictation before transcribing a file",
+    )?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _busy = busy;
+        transcribe_long_audio(&settings, &app, &state, &path, timestamps)
+    })
+    .await
+    .map_err(|err| format!("Transcription task failed: {err}"))?
+}
+
+/// Also checks for ffmpeg, since every format but WAV is decoded by it.
+fn validate_audio_file(path: String) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(format!("Audio file not found: {}", path.display()).into());
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let is_audio = TRANSCRIBABLE_EXTENSIONS
+        .iter()
+        .any(|supported| extension.eq_ignore_ascii_case(supported));
+    if !is_audio {
+        return Err(format!(
+            "Unsupported audio file: {}. Expected one of: {}",
+            path.display(),
+            TRANSCRIBABLE_EXTENSIONS.join(", ")
+        )
+        .into());
+    }
+
+    if !extension.eq_ignore_ascii_case("wav") {
+        check_ffmpeg().map_err(|err| AppError::new(ErrorCode::DependencyMissing, err))?;
+    }
+
+    Ok(path)
+}
+
+/// Transcribes `path` with every model in turn, so their accuracy and speed
+/// can be compared on this machine. A model that is not cached yet is
+/// downloaded first, which shows up in its `elapsed_ms`. The runtime counts
+/// as transcribing for the whole run, so dictation can't start in between.
+fn benchmark_models_internal(
     app: &AppHandle,
     state: &Arc<AppRuntime>,
-    shortcut_text: &str,
-) -> Result<String, String> {
-    let normalized_shortcut = normalize_shortcut_text(shortcut_text)?;
+    path: &Path,
+) -> Result<Vec<ModelBenchmark>, AppError> {
+    if !is_runtime_ready(state)? {
+        return Err(AppError::new(ErrorCode::NotReady, RUNTIME_NOT_READY));
+    }
 
-    let shortcut: Shortcut = normalized_shortcut
-        .parse()
-        .map_err(|err| format!("Invalid shortcut '{normalized_shortcut}': {err}"))?;
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
 
-    app.global_shortcut()
-        .unregister_all()
-        .map_err(|err| format!("Failed to clear previous shortcuts: {err}"))?;
+    let _busy = BusyPhase::claim(
+        app,
+        state,
+        "Finish the current dictation before benchmarking models",
+    )?;
 
-    let state_for_handler = state.clone();
-    app.global_shortcut()
-        .on_shortcut(shortcut, move |_app_handle, _shortcut, event| {
-            let settings = match state_for_handler.settings.lock() {
-                Ok(settings) => settings.clone(),
-                Err(_) => return,
+    Ok(ModelOption::ALL
+        .into_iter()
+        .map(|model| {
+            let settings = AppSettings {
+                model,
+                ..settings.clone()
             };
-
-            match settings.recording_mode {
-                RecordingMode::Hold => {
-                    if event.state == ShortcutState::Pressed {
-                        let _ = start_dictation_internal(&state_for_handler);
-                    }
-
-                    if event.state == ShortcutState::Released {
-                        let _ = stop_dictation_internal(&state_for_handler);
-                    }
-                }
-                RecordingMode::Toggle => {
-                    if event.state == ShortcutState::Pressed {
-                        let _ = toggle_dictation_internal(&state_for_handler);
-                    }
-                }
+            let started = Instant::now();
+            let transcription = transcribe_audio(&settings, app, state, path, false);
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+
+            match transcription {
+                Ok(transcription) => ModelBenchmark {
+                    model,
+                    transcript: Some(transcription.text),
+                    elapsed_ms,
+                    inference_ms: transcription.duration_ms,
+                    error: None,
+                },
+                Err(err) => ModelBenchmark {
+                    model,
+                    transcript: None,
+                    elapsed_ms,
+                    inference_ms: None,
+                    error: Some(err.message),
+                },
             }
         })
-        .map_err(|err| format!("Failed to register shortcut handler: {err}"))?;
+        .collect())
+}
 
-    *state
-        .registered_shortcut
+#[tauri::command]
+async fn transcribe_file(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    path: String,
+) -> Result<String, AppError> {
+    transcribe_file_internal(app, state.inner().clone(), path, false)
+        .await
+        .map(|transcription| transcription.text)
+}
+
+/// Like `transcribe_file`, plus segment start/end times for captions.
+#[tauri::command]
+async fn transcribe_file_with_timestamps(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    path: String,
+) -> Result<TimestampedTranscript, AppError> {
+    let transcription = transcribe_file_internal(app, state.inner().clone(), path, true).await?;
+
+    Ok(TimestampedTranscript {
+        text: transcription.text,
+        language: transcription.language,
+        segments: transcription.segments,
+    })
+}
+
+#[tauri::command]
+async fn benchmark_models(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    sample_path: String,
+) -> Result<Vec<ModelBenchmark>, AppError> {
+    let path = validate_audio_file(sample_path)?;
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || benchmark_models_internal(&app, &state, &path))
+        .await
+        .map_err(|err| format!("Benchmark task failed: {err}"))?
+}
+
+#[tauri::command]
+async fn warmup(app: AppHandle, state: State<'_, Arc<AppRuntime>>) -> Result<(), AppError> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || warmup_internal(&app, &state))
+        .await
+        .map_err(|err| format!("Warmup task failed: {err}"))?
+}
+
+#[tauri::command]
+async fn test_microphone(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    seconds: Option<u64>,
+) -> Result<MicrophoneTest, AppError> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        test_microphone_internal(&app, &state, seconds.unwrap_or(3))
+    })
+    .await
+    .map_err(|err| format!("Microphone test task failed: {err}"))?
+}
+
+#[tauri::command]
+fn get_pending_transcript(state: State<'_, Arc<AppRuntime>>) -> Result<Option<String>, AppError> {
+    state
+        .pending_transcripts
         .lock()
-        .map_err(|_| "Failed to lock shortcut state".to_string())? = normalized_shortcut.clone();
+        .map(|pending| pending.front().map(|pending| pending.text.clone()))
+        .map_err(|_| "Failed to lock pending transcript".to_string().into())
+}
 
-    Ok(normalized_shortcut)
+#[tauri::command]
+async fn confirm_injection(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    text: Option<String>,
+) -> Result<(), AppError> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || confirm_injection_internal(&app, &state, text))
+        .await
+        .map_err(|err| format!("Injection task failed: {err}"))?
 }
 
-fn install_tray(app: &AppHandle, state: Arc<AppRuntime>) -> Result<(), String> {
-    let open_item = MenuItem::with_id(app, "open", "Open Settings", true, None::<&str>)
-        .map_err(|err| err.to_string())?;
-    let toggle_item =
-        MenuItem::with_id(app, "toggle", "Start / Stop Dictation", true, None::<&str>)
-            .map_err(|err| err.to_string())?;
-    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)
-        .map_err(|err| err.to_string())?;
+#[tauri::command]
+fn discard_transcript(app: AppHandle, state: State<'_, Arc<AppRuntime>>) -> Result<(), AppError> {
+    discard_transcript_internal(&app, state.inner()).map_err(AppError::from)
+}
 
-    let menu = Menu::with_items(app, &[&open_item, &toggle_item, &quit_item])
-        .map_err(|err| err.to_string())?;
+#[tauri::command]
+fn abort_transcription(state: State<'_, Arc<AppRuntime>>) -> Result<(), AppError> {
+    abort_transcription_internal(state.inner()).map_err(AppError::from)
+}
 
-    let state_for_menu = state.clone();
-    let mut tray_builder = TrayIconBuilder::with_id("dictation-tray");
+#[tauri::command]
+fn list_input_devices() -> Result<Vec<String>, AppError> {
+    list_input_devices_internal()
+}
 
-    if let Some(icon) = app.default_window_icon() {
-        tray_builder = tray_builder.icon(icon.clone());
-    }
+#[tauri::command]
+fn validate_input_device(state: State<'_, Arc<AppRuntime>>) -> Result<bool, AppError> {
+    validate_input_device_internal(state.inner())
+}
 
-    tray_builder
-        .tooltip("Delulu Talks")
-        .menu(&menu)
-        .show_menu_on_left_click(true)
-        .on_menu_event(move |app_handle, event| match event.id().as_ref() {
-            "open" => {
-                let _ = show_settings_window(app_handle);
-            }
-            "toggle" => {
-                let _ = toggle_dictation_internal(&state_for_menu);
-            }
-            "quit" => {
-                app_handle.exit(0);
-            }
-            _ => {}
-        })
-        .build(app)
-        .map_err(|err| format!("Failed to create tray icon: {err}"))?;
+#[tauri::command]
+fn list_output_devices() -> Result<Vec<String>, AppError> {
+    list_output_devices_internal().map_err(AppError::from)
+}
 
-    Ok(())
+#[tauri::command]
+async fn list_input_device_details() -> Result<Vec<InputDeviceInfo>, AppError> {
+    tauri::async_runtime::spawn_blocking(list_input_device_details_internal)
+        .await
+        .map_err(|err| format!("Device query task failed: {err}"))?
 }
 
 #[tauri::command]
-fn get_settings(state: State<'_, Arc<AppRuntime>>) -> Result<AppSettings, String> {
-    state
+fn normalize_shortcut(shortcut: String) -> Result<String, AppError> {
+    normalize_shortcut_text(&shortcut).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn reregister_shortcut(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+) -> Result<String, AppError> {
+    reregister_current_shortcut(&app, state.inner())
+}
+
+#[tauri::command]
+fn get_registered_shortcut(
+    state: State<'_, Arc<AppRuntime>>,
+) -> Result<RegisteredShortcut, AppError> {
+    let shortcut = state
+        .registered_shortcut
+        .lock()
+        .map_err(|_| "Failed to lock shortcut state".to_string())?
+        .clone();
+    let recording_mode = state
         .settings
         .lock()
-        .map(|settings| settings.clone())
-        .map_err(|_| "Failed to lock settings".to_string())
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .recording_mode;
+
+    Ok(RegisteredShortcut {
+        shortcut,
+        recording_mode,
+    })
 }
 
 #[tauri::command]
-fn list_input_devices() -> Result<Vec<String>, String> {
-    list_input_devices_internal()
+fn pin_language(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    language: String,
+) -> Result<AppSettings, AppError> {
+    pin_language_internal(&app, state.inner(), &language).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn normalize_shortcut(shortcut: String) -> Result<String, String> {
-    normalize_shortcut_text(&shortcut)
+fn switch_profile(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    name: String,
+) -> Result<AppSettings, AppError> {
+    switch_profile_internal(&app, state.inner(), &name)
 }
 
 #[tauri::command]
@@ -1163,9 +7266,201 @@ fn update_settings(
     app: AppHandle,
     state: State<'_, Arc<AppRuntime>>,
     mut settings: AppSettings,
-) -> Result<AppSettings, String> {
+) -> Result<AppSettings, AppError> {
+    for profile in &mut settings.profiles {
+        profile.name = profile.name.trim().to_string();
+        if profile.name.is_empty() {
+            return Err("Shortcut profile names cannot be empty".to_string().into());
+        }
+    }
+
+    if let Some(max_new_tokens) = settings.max_new_tokens {
+        if !MAX_NEW_TOKENS_RANGE.contains(&max_new_tokens) {
+            return Err(format!(
+                "Max new tokens must be between {} and {}",
+                MAX_NEW_TOKENS_RANGE.start(),
+                MAX_NEW_TOKENS_RANGE.end()
+            )
+            .into());
+        }
+    }
+    if let Some(beam_size) = settings.beam_size {
+        if !BEAM_SIZE_RANGE.contains(&beam_size) {
+            return Err(format!(
+                "Beam size must be between {} and {}",
+                BEAM_SIZE_RANGE.start(),
+                BEAM_SIZE_RANGE.end()
+            )
+            .into());
+        }
+    }
+    if let Some(sample_rate) = settings.capture_sample_rate {
+        if !CAPTURE_SAMPLE_RATE_RANGE.contains(&sample_rate) {
+            return Err(format!(
+                "Capture sample rate must be between {} and {} Hz",
+                CAPTURE_SAMPLE_RATE_RANGE.start(),
+                CAPTURE_SAMPLE_RATE_RANGE.end()
+            )
+            .into());
+        }
+        // A disconnected device is checked again when recording starts.
+        if settings.capture_source == CaptureSource::Microphone {
+            if let Ok(device) = resolve_input_device(&settings) {
+                if pinned_input_config(&device, sample_rate).is_none() {
+                    return Err(format!(
+                        "'{}' cannot capture at {sample_rate} Hz",
+                        device
+                            .name()
+                            .unwrap_or_else(|_| "The microphone".to_string())
+                    )
+                    .into());
+                }
+            }
+        }
+    }
+    if let Some(temperature) = settings.temperature {
+        if !TEMPERATURE_RANGE.contains(&temperature) {
+            return Err(format!(
+                "Temperature must be between {} and {}",
+                TEMPERATURE_RANGE.start(),
+                TEMPERATURE_RANGE.end()
+            )
+            .into());
+        }
+    }
+    settings
+        .overlay_messages
+        .retain(|_, template| !template.trim().is_empty());
+    settings.paste_template = settings
+        .paste_template
+        .take()
+        .filter(|template| !template.trim().is_empty());
+    settings.wake_word = settings
+        .wake_word
+        .as_deref()
+        .map(str::trim)
+        .filter(|wake_word| !wake_word.is_empty())
+        .map(str::to_string);
+    settings.model_cache_dir = settings
+        .model_cache_dir
+        .take()
+        .filter(|dir| !dir.as_os_str().is_empty());
+    if let Some(template) = &settings.paste_template {
+        if !template.contains(TEMPLATE_PLACEHOLDER) {
+            return Err(format!(
+                "Paste template must contain {TEMPLATE_PLACEHOLDER} where the transcript goes"
+            )
+            .into());
+        }
+    }
+
+    settings.python_command = normalize_python_command(&settings.python_command);
+    let python_changed = state
+        .settings
+        .lock()
+        .map(|current| {
+            current.python_command != settings.python_command
+                || current.use_bundled_python != settings.use_bundled_python
+        })
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    // Caught here rather than minutes later during the bootstrap.
+    if python_changed {
+        ensure_python_binary(&settings)?;
+    }
+
+    let (previous_model, previous_language) = state
+        .settings
+        .lock()
+        .map(|current| (current.model, current.language.clone()))
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    if settings.model != previous_model && settings.language == previous_language {
+        if let Some(language) = settings.model_languages.get(&settings.model) {
+            settings.language = language.clone();
+        }
+    }
+    settings
+        .model_languages
+        .insert(settings.model, settings.language.clone());
+
     let normalized_shortcut = register_shortcut(&app, state.inner(), &settings.shortcut)?;
+    register_mouse_trigger(&app, state.inner(), settings.mouse_trigger)?;
+    settings.version = SETTINGS_VERSION;
     settings.shortcut = normalized_shortcut;
+    settings.injection_delay_ms = settings.injection_delay_ms.min(MAX_INJECTION_DELAY_MS);
+    settings.overlay_hide_delay_ms = settings
+        .overlay_hide_delay_ms
+        .min(MAX_OVERLAY_HIDE_DELAY_MS);
+    settings.type_chunk_delay_ms = settings.type_chunk_delay_ms.min(MAX_TYPE_CHUNK_DELAY_MS);
+    settings.recorder_retries = settings.recorder_retries.min(MAX_RECORDER_RETRIES);
+    if settings.chunk_length_secs > 0 {
+        settings.chunk_length_secs = settings
+            .chunk_length_secs
+            .clamp(*CHUNK_LENGTH_RANGE.start(), *CHUNK_LENGTH_RANGE.end());
+    }
+    settings.chunk_overlap_secs = settings
+        .chunk_overlap_secs
+        .min(settings.chunk_length_secs / 4);
+    if settings.progress_interval_ms > 0 {
+        settings.progress_interval_ms = settings
+            .progress_interval_ms
+            .clamp(MIN_PROGRESS_INTERVAL_MS, MAX_PROGRESS_INTERVAL_MS);
+    }
+    settings.silence_threshold = settings.silence_threshold.clamp(0.0, 1.0);
+    settings.trim_threshold = settings.trim_threshold.clamp(0.0, 1.0);
+    settings.active_profile = settings
+        .active_profile
+        .filter(|&index| index < settings.profiles.len());
+    if let Some(index) = settings.active_profile {
+        // The flat shortcut fields always mirror the active profile.
+        settings.profiles[index].shortcut = settings.shortcut.clone();
+        settings.profiles[index].recording_mode = settings.recording_mode;
+    }
+    settings
+        .app_profiles
+        .retain(|profile| !normalize_app_name(&profile.app).is_empty());
+    for profile in &mut settings.app_profiles {
+        profile.app = profile.app.trim().to_string();
+        profile.injection_delay_ms = profile.injection_delay_ms.min(MAX_INJECTION_DELAY_MS);
+    }
+    settings
+        .injection_denylist
+        .retain(|app| !normalize_app_name(app).is_empty());
+    for app in &mut settings.injection_denylist {
+        *app = app.trim().to_string();
+    }
+    settings.venv_path = settings.venv_path.trim().to_string();
+    settings.dependency_pins = settings
+        .dependency_pins
+        .iter()
+        .map(|pin| pin.trim().to_string())
+        .filter(|pin| !pin.is_empty())
+        .collect();
+    settings.post_process_command = settings
+        .post_process_command
+        .as_deref()
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .map(str::to_string);
+    settings
+        .fallback_devices
+        .retain(|device| !device.trim().is_empty());
+    settings.filler_words = settings
+        .filler_words
+        .iter()
+        .map(|word| word.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect();
+    for command in &mut settings.voice_commands {
+        command.phrase = command
+            .phrase
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+    }
+    settings
+        .voice_commands
+        .retain(|command| !command.phrase.is_empty());
     save_settings(&app, &settings)?;
 
     let mut current = state
@@ -1173,13 +7468,31 @@ fn update_settings(
         .lock()
         .map_err(|_| "Failed to lock settings".to_string())?;
 
+    let theme_changed = current.theme != settings.theme;
     let should_rebootstrap = current.python_command != settings.python_command
+        || current.use_bundled_python != settings.use_bundled_python
+        || current.venv_path != settings.venv_path
+        || current.model_cache_dir != settings.model_cache_dir
         || current.model != settings.model
-        || current.language != settings.language;
+        || current.language != settings.language
+        || current.compute_device != settings.compute_device;
+    let wake_changed = current.wake_word != settings.wake_word
+        || (settings.wake_word.is_some()
+            && (should_rebootstrap
+                || current.input_device != settings.input_device
+                || current.silence_threshold != settings.silence_threshold));
 
     *current = settings.clone();
     drop(current);
 
+    sync_overlay_window(&app, &settings)?;
+    if wake_changed {
+        register_wake_word(&app, state.inner(), &settings)?;
+    }
+    if theme_changed {
+        emit_theme(&app, settings.theme);
+    }
+
     if should_rebootstrap {
         let _ = set_runtime_ready(state.inner(), false);
         spawn_bootstrap_task(app.clone(), state.inner().clone(), settings.clone());
@@ -1188,29 +7501,115 @@ fn update_settings(
     Ok(settings)
 }
 
+/// Writes default settings and applies them like a save that changed
+/// everything, including a fresh bootstrap.
+#[tauri::command]
+fn reset_settings(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+) -> Result<AppSettings, AppError> {
+    let mut settings = AppSettings::default();
+    settings.shortcut = register_shortcut(&app, state.inner(), &settings.shortcut)?;
+    register_mouse_trigger(&app, state.inner(), settings.mouse_trigger)?;
+    register_wake_word(&app, state.inner(), &settings)?;
+    save_settings(&app, &settings)?;
+
+    *state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())? = settings.clone();
+
+    sync_overlay_window(&app, &settings)?;
+    emit_theme(&app, settings.theme);
+
+    let _ = set_runtime_ready(state.inner(), false);
+    spawn_bootstrap_task(app.clone(), state.inner().clone(), settings.clone());
+
+    Ok(settings)
+}
+
+/// Starts ASR setup again with the current settings, e.g. after a failed
+/// dependency install was fixed by hand.
+#[tauri::command]
+fn retry_bootstrap(app: AppHandle, state: State<'_, Arc<AppRuntime>>) -> Result<(), AppError> {
+    if current_phase(state.inner())? != RuntimePhase::Idle {
+        return Err("Finish the current dictation before retrying ASR setup"
+            .to_string()
+            .into());
+    }
+
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+
+    let _ = set_runtime_ready(state.inner(), false);
+    spawn_bootstrap_task(app.clone(), state.inner().clone(), settings);
+    Ok(())
+}
+
+#[tauri::command]
+fn test_injection(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    text: Option<String>,
+) -> Result<(), AppError> {
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+    if !settings.auto_inject {
+        return Err("Automatic injection is turned off".to_string().into());
+    }
+    let text = text
+        .filter(|text| !text.is_empty())
+        .unwrap_or_else(|| TEST_INJECTION_TEXT.to_string());
+
+    // Give the user time to focus the target app before injecting.
+    thread::spawn(move || {
+        thread::sleep(TEST_INJECTION_COUNTDOWN);
+        if let Err(err) = inject_text_at_cursor(&text, &settings) {
+            emit_status(&app, DictationPhase::Error, Some(err));
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+fn start_dictation(app: AppHandle, state: State<'_, Arc<AppRuntime>>) -> Result<(), AppError> {
+    start_dictation_internal(&app, state.inner()).map_err(AppError::from)
+}
+
 #[tauri::command]
-fn start_dictation(state: State<'_, Arc<AppRuntime>>) -> Result<(), String> {
-    start_dictation_internal(state.inner())
+fn stop_dictation(app: AppHandle, state: State<'_, Arc<AppRuntime>>) -> Result<(), AppError> {
+    stop_dictation_internal(&app, state.inner()).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn stop_dictation(state: State<'_, Arc<AppRuntime>>) -> Result<(), String> {
-    stop_dictation_internal(state.inner())
+fn toggle_dictation(app: AppHandle, state: State<'_, Arc<AppRuntime>>) -> Result<(), AppError> {
+    toggle_dictation_internal(&app, state.inner()).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn toggle_dictation(state: State<'_, Arc<AppRuntime>>) -> Result<(), String> {
-    toggle_dictation_internal(state.inner())
+fn set_dictation_enabled(
+    app: AppHandle,
+    state: State<'_, Arc<AppRuntime>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    set_dictation_enabled_internal(&app, state.inner(), enabled).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn open_settings_window(app: AppHandle) -> Result<(), String> {
-    show_settings_window(&app)
+fn open_settings_window(app: AppHandle) -> Result<(), AppError> {
+    show_settings_window(&app).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn hide_settings(app: AppHandle) -> Result<(), String> {
-    hide_settings_window(&app)
+fn hide_settings(app: AppHandle) -> Result<(), AppError> {
+    hide_settings_window(&app).map_err(AppError::from)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -1219,21 +7618,52 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(|app| {
+            let _ = BUNDLED_PYTHON.set(resolve_bundled_python(app.handle()));
             let initial_settings = load_settings(app.handle());
+            cleanup_stale_recordings(app.handle());
             let (worker_tx, worker_rx) = mpsc::channel::<WorkerCommand>();
 
             let runtime = Arc::new(AppRuntime {
                 settings: Mutex::new(initial_settings.clone()),
                 phase: Mutex::new(RuntimePhase::Idle),
                 ready: Mutex::new(false),
+                enabled: Mutex::new(true),
+                enabled_menu_item: Mutex::new(None),
+                last_activation: Mutex::new(None),
+                modifier_watch_generation: Mutex::new(0),
+                mouse_watch_generation: Mutex::new(0),
+                wake_watch_generation: Mutex::new(0),
+                overlay_hide_generation: Mutex::new(0),
+                pending_transcripts: Mutex::new(VecDeque::new()),
+                transcription_child: Mutex::new(None),
+                transcription_stage: Mutex::new(None),
                 bootstrap_lock: Mutex::new(()),
+                bootstrap_generation: Mutex::new(0),
                 registered_shortcut: Mutex::new(initial_settings.shortcut.clone()),
+                performance: Mutex::new(PerformanceHistory::default()),
+                recordings_dir: Mutex::new(None),
+                recording_counter: Mutex::new(0),
+                queued_start: Mutex::new(false),
+                language_streak: Mutex::new(None),
                 worker_tx,
             });
 
             app.manage(runtime.clone());
+            // A shortcut saved before it became unusable must not stop the app
+            // from launching.
             let normalized_shortcut =
-                register_shortcut(app.handle(), &runtime, &initial_settings.shortcut)?;
+                match register_shortcut(app.handle(), &runtime, &initial_settings.shortcut) {
+                    Ok(shortcut) => shortcut,
+                    Err(err) => {
+                        eprintln!("{err}, falling back to the default shortcut");
+                        register_shortcut(app.handle(), &runtime, &AppSettings::default().shortcut)?
+                    }
+                };
+            if let Err(err) =
+                register_mouse_trigger(app.handle(), &runtime, initial_settings.mouse_trigger)
+            {
+                eprintln!("mouse trigger unavailable: {err}");
+            }
 
             if normalized_shortcut != initial_settings.shortcut {
                 let mut loaded_settings = initial_settings.clone();
@@ -1251,39 +7681,283 @@ pub fn run() {
                 run_worker_loop(app_handle_for_worker, runtime_for_worker, worker_rx)
             });
 
-            ensure_overlay_window(app.handle())?;
+            spawn_resume_watcher(app.handle().clone(), runtime.clone());
+            spawn_device_watcher(app.handle().clone(), runtime.clone());
+
+            sync_overlay_window(app.handle(), &initial_settings)?;
+            ensure_review_window(app.handle())?;
             install_tray(app.handle(), runtime.clone())?;
 
             if let Some(main_window) = app.get_webview_window("main") {
                 let window_handle = main_window.clone();
-                main_window.on_window_event(move |event| {
-                    if let WindowEvent::CloseRequested { api, .. } = event {
+                let state_for_window = runtime.clone();
+                main_window.on_window_event(move |event| match event {
+                    WindowEvent::CloseRequested { api, .. } => {
                         api.prevent_close();
                         let _ = window_handle.hide();
                     }
+                    WindowEvent::ThemeChanged(_) => {
+                        let preference = state_for_window
+                            .settings
+                            .lock()
+                            .map(|settings| settings.theme)
+                            .unwrap_or(ThemePreference::System);
+                        if preference == ThemePreference::System {
+                            emit_theme(window_handle.app_handle(), preference);
+                        }
+                    }
+                    _ => {}
                 });
             }
 
+            // The main window is created hidden, so only showing needs doing.
+            if !initial_settings.start_minimized {
+                if let Err(err) = show_settings_window(app.handle()) {
+                    eprintln!("{err}");
+                }
+            }
+
             let bootstrap_settings = runtime
                 .settings
                 .lock()
                 .map_err(|_| "Failed to lock settings".to_string())?
                 .clone();
+            register_wake_word(app.handle(), &runtime, &bootstrap_settings)?;
             spawn_bootstrap_task(app.handle().clone(), runtime.clone(), bootstrap_settings);
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_settings,
+            get_performance_stats,
+            get_dictation_status,
+            run_diagnostics,
+            query_environment,
+            transcribe_file,
+            transcribe_file_with_timestamps,
+            benchmark_models,
+            warmup,
+            retry_bootstrap,
+            test_microphone,
+            get_pending_transcript,
+            confirm_injection,
+            discard_transcript,
+            abort_transcription,
             list_input_devices,
+            validate_input_device,
+            list_input_device_details,
+            list_output_devices,
+            get_recordings_usage,
+            prune_recordings,
+            get_theme,
+            get_history,
+            copy_history_entry,
+            open_history,
             normalize_shortcut,
+            reregister_shortcut,
+            get_registered_shortcut,
+            switch_profile,
+            pin_language,
             update_settings,
+            quit_app,
+            reset_settings,
+            test_injection,
             start_dictation,
             stop_dictation,
             toggle_dictation,
+            set_dictation_enabled,
             open_settings_window,
+            open_recordings_folder,
             hide_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(code: &str) -> String {
+        code.parse::<Shortcut>()
+            .unwrap_or_else(|err| panic!("{code} should parse: {err}"))
+            .into_string()
+    }
+
+    fn assert_normalizes(cases: &[(&str, &str)]) {
+        for (input, code) in cases {
+            assert_eq!(
+                normalize_shortcut_text(input).as_deref(),
+                Ok(canonical(code).as_str()),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn normalizes_numpad_digits() {
+        for digit in 0..=9 {
+            let code = format!("Numpad{digit}");
+            for input in [
+                format!("Numpad{digit}"),
+                format!("Numpad {digit}"),
+                format!("KP_{digit}"),
+                format!("num{digit}"),
+            ] {
+                assert_normalizes(&[(input.as_str(), code.as_str())]);
+            }
+        }
+    }
+
+    #[test]
+    fn normalizes_numpad_keys() {
+        assert_normalizes(&[
+            ("Numpad Enter", "NumpadEnter"),
+            ("KP_Enter", "NumpadEnter"),
+            ("num return", "NumpadEnter"),
+            ("KP_Add", "NumpadAdd"),
+            ("numpad plus", "NumpadAdd"),
+            ("num-", "NumpadSubtract"),
+            ("Numpad-", "NumpadSubtract"),
+            ("KP_Subtract", "NumpadSubtract"),
+            ("numpad minus", "NumpadSubtract"),
+            ("Numpad*", "NumpadMultiply"),
+            ("KP_Multiply", "NumpadMultiply"),
+            ("Numpad/", "NumpadDivide"),
+            ("KP_Divide", "NumpadDivide"),
+            ("Numpad.", "NumpadDecimal"),
+            ("KP_Decimal", "NumpadDecimal"),
+            ("Numpad=", "NumpadEqual"),
+            ("KP_Equal", "NumpadEqual"),
+            ("Ctrl+Numpad Enter", "Control+NumpadEnter"),
+        ]);
+    }
+
+    #[test]
+    fn normalizes_punctuation_keys() {
+        assert_normalizes(&[
+            (",", "Comma"),
+            ("comma", "Comma"),
+            (".", "Period"),
+            ("period", "Period"),
+            (";", "Semicolon"),
+            ("semicolon", "Semicolon"),
+            ("'", "Quote"),
+            ("quote", "Quote"),
+            ("`", "Backquote"),
+            ("backquote", "Backquote"),
+            ("backtick", "Backquote"),
+            ("[", "BracketLeft"),
+            ("bracketleft", "BracketLeft"),
+            ("]", "BracketRight"),
+            ("bracketright", "BracketRight"),
+            ("\\", "Backslash"),
+            ("backslash", "Backslash"),
+            ("/", "Slash"),
+            ("slash", "Slash"),
+            ("-", "Minus"),
+            ("minus", "Minus"),
+            ("=", "Equal"),
+            ("equal", "Equal"),
+            ("Ctrl+Shift+comma", "Control+Shift+Comma"),
+        ]);
+    }
+
+    fn assert_voice_commands(cases: &[(&str, &str)]) {
+        let commands = default_voice_commands();
+        for (spoken, written) in cases {
+            assert_eq!(
+                replace_voice_commands(spoken, &commands),
+                *written,
+                "{spoken}"
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_sentence_commands_inside_a_sentence() {
+        assert_voice_commands(&[
+            ("the trial period ended", "the trial period ended"),
+            ("a full stop sign", "a full stop sign"),
+        ]);
+    }
+
+    #[test]
+    fn replaces_commands_at_sentence_boundaries() {
+        assert_voice_commands(&[
+            ("that is all period", "that is all."),
+            ("one period Two", "one. Two"),
+            ("stop period. Next", "stop. Next"),
+            ("Done. period", "Done."),
+        ]);
+    }
+
+    #[test]
+    fn replaces_inline_punctuation_commands() {
+        assert_voice_commands(&[
+            ("hello comma world", "hello, world"),
+            ("hello comma, world", "hello, world"),
+            ("the periodic table", "the periodic table"),
+        ]);
+    }
+
+    #[test]
+    fn prefers_the_longer_line_break_command() {
+        assert_voice_commands(&[
+            ("first new paragraph second", "first\n\nSecond"),
+            ("first new line second", "first\nSecond"),
+        ]);
+    }
+
+    #[test]
+    fn stitches_away_the_overlapping_words() {
+        assert_eq!(
+            stitch_transcripts("the quick brown fox", "brown fox jumps over"),
+            "the quick brown fox jumps over"
+        );
+        assert_eq!(
+            stitch_transcripts("Hello there, General", "general Kenobi"),
+            "Hello there, General Kenobi"
+        );
+        assert_eq!(stitch_transcripts("a b a b", "a b c"), "a b a b c");
+        assert_eq!(stitch_transcripts("same words", "same words"), "same words");
+    }
+
+    #[test]
+    fn stitches_without_overlap() {
+        assert_eq!(
+            stitch_transcripts("one two three", "four five"),
+            "one two three four five"
+        );
+        assert_eq!(stitch_transcripts("", "first words"), "first words");
+        assert_eq!(stitch_transcripts("already done ", ""), "already done");
+    }
+
+    #[test]
+    fn keeps_whole_history_lines_within_budget() {
+        let raw = "{\"a\":1}\n{\"b\":22}\n{\"c\":333}\n";
+        assert_eq!(newest_history_lines(raw, raw.len()), raw);
+        assert_eq!(newest_history_lines(raw, 19), "{\"b\":22}\n{\"c\":333}\n");
+        assert_eq!(newest_history_lines(raw, 18), "{\"c\":333}\n");
+        assert_eq!(newest_history_lines(raw, 5), "");
+        assert_eq!(newest_history_lines("é\nà\n", 3), "à\n");
+    }
+
+    fn focus(app: Option<&str>, window: Option<u64>) -> FocusTarget {
+        FocusTarget {
+            app: app.map(str::to_string),
+            window,
+        }
+    }
+
+    #[test]
+    fn focus_target_tells_windows_of_one_app_apart() {
+        let target = focus(Some("Code.exe"), Some(7));
+        assert!(target.matches(&focus(Some("code"), Some(7))));
+        assert!(!target.matches(&focus(Some("Code.exe"), Some(8))));
+        assert!(target.matches(&focus(Some("code"), None)));
+        assert!(!target.matches(&focus(Some("firefox"), None)));
+        assert!(!target.matches(&focus(None, None)));
+        assert!(focus(None, None).matches(&focus(Some("firefox"), Some(3))));
+    }
+}
@@ -1,30 +1,219 @@
-import { useEffect, useMemo, useState, type ReactElement } from "react";
+import { useEffect, useMemo, useRef, useState, type ReactElement } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 
 type RecordingMode = "hold" | "toggle";
+type MouseButton = "middle" | "back" | "forward";
+type CaptureSource = "microphone" | "systemLoopback";
+type OverlayMonitor = "focusedWindow" | "primary";
+type ThemePreference = "system" | "light" | "dark";
+type OutputMode = "injectAtCursor" | "clipboardOnly" | "both" | "confirmBeforeInject";
+type InjectionMethod = "paste" | "typing";
 type ModelOption = "qwen3Asr17b" | "qwen3Asr06b";
+type ComputeDevice = "auto" | "cpu" | "cuda" | "mps";
+type RecordingFormat = "pcm16" | "pcm24" | "float32";
 type DictationPhase =
   | "idle"
   | "bootstrapping"
   | "listening"
   | "transcribing"
   | "error";
+type BootstrapStage =
+  | "checkingPython"
+  | "installingDeps"
+  | "downloadingModel"
+  | "warmingUp"
+  | "ready"
+  | "failed";
+
+type VoiceCommand = {
+  phrase: string;
+  output: string;
+};
+
+type AppInjectionProfile = {
+  app: string;
+  injectionMethod: InjectionMethod;
+  injectionDelayMs: number;
+};
+
+type ShortcutProfile = {
+  name: string;
+  shortcut: string;
+  recordingMode: RecordingMode;
+};
 
 type SettingsTab = "general" | "speech" | "audio" | "runtime";
 
 type AppSettings = {
+  version: number;
   shortcut: string;
   recordingMode: RecordingMode;
+  queueNextDictation: boolean;
   model: ModelOption;
   language: string;
   pythonCommand: string;
   inputDevice: string;
+  outputDevice: string;
+  fallbackDevices: string[];
+  injectionDelayMs: number;
+  outputMode: OutputMode;
+  injectionMethod: InjectionMethod;
+  appProfiles: AppInjectionProfile[];
+  silenceThreshold: number;
+  quietEmptyTranscripts: boolean;
+  computeDevice: ComputeDevice;
+  audioCues: boolean;
+  trimThreshold: number;
+  trimMarginMs: number;
+  profiles: ShortcutProfile[];
+  activeProfile: number | null;
+  clipboardFallback: boolean;
+  recordingFormat: RecordingFormat;
+  focusGuard: boolean;
+  unfocusedGuard: boolean;
+  injectionDenylist: string[];
+  modelLanguages: Partial<Record<ModelOption, string>>;
+  languagePinAfter: number;
+  autoPinLanguage: boolean;
+  queueWhileBootstrapping: boolean;
+  venvPath: string;
+  useBundledPython: boolean;
+  modelCacheDir: string | null;
+  autoInstallDeps: boolean;
+  dependencyPins: string[];
+  overlayHideDelayMs: number;
+  removeFillers: boolean;
+  fillerWords: string[];
+  applyVoiceCommands: boolean;
+  voiceCommands: VoiceCommand[];
+  restoreClipboard: boolean;
+  keepRecordings: boolean;
+  saveHistory: boolean;
+  typeChunkSize: number;
+  typeChunkDelayMs: number;
+  mouseTrigger: MouseButton | null;
+  wakeWord: string | null;
+  captureSource: CaptureSource;
+  autoInject: boolean;
+  recorderRetries: number;
+  captureSampleRate: number | null;
+  progressIntervalMs: number;
+  postProcessCommand: string | null;
+  overlayClickable: boolean;
+  overlayMonitor: OverlayMonitor;
+  overlayMessages: Partial<Record<DictationPhase, string>>;
+  showOverlay: boolean;
+  startMinimized: boolean;
+  maxNewTokens: number | null;
+  beamSize: number | null;
+  temperature: number | null;
+  theme: ThemePreference;
+  appendSuffix: string;
+  pasteTemplate: string | null;
+  chunkLengthSecs: number;
+  chunkOverlapSecs: number;
 };
 
 type DictationStatus = {
   phase: DictationPhase;
   message?: string | null;
+  stage?: BootstrapStage | null;
+  overlayText?: string | null;
+};
+
+type BootstrapProgress = {
+  stage: BootstrapStage;
+  // Last step that started, kept so a failure can be pinned to it.
+  reached: BootstrapStage;
+};
+
+const bootstrapSteps: { stage: BootstrapStage; label: string }[] = [
+  { stage: "checkingPython", label: "Python runtime" },
+  { stage: "installingDeps", label: "Dependencies" },
+  { stage: "downloadingModel", label: "Model download" },
+  { stage: "warmingUp", label: "Model warm-up" },
+  { stage: "ready", label: "Ready" },
+];
+
+type SelectedInputDevice = {
+  name: string;
+  fellBack: boolean;
+};
+
+type InputDeviceInfo = {
+  name: string;
+  sampleRate: number | null;
+  channels: number | null;
+  sampleFormat: string | null;
+};
+
+type TranscriptionStage = "loading" | "decoding";
+
+type TranscriptionProgress = {
+  stage: TranscriptionStage | null;
+  elapsedMs: number;
+};
+
+type RegisteredShortcut = {
+  shortcut: string;
+  recordingMode: RecordingMode;
+};
+
+type RecordingsUsage = {
+  totalBytes: number;
+  recordingCount: number;
+};
+
+type ModelBenchmark = {
+  model: ModelOption;
+  transcript: string | null;
+  elapsedMs: number;
+  inferenceMs: number | null;
+  error: string | null;
+};
+
+type LanguageSuggestion = {
+  language: string;
+  detections: number;
+  pinned: boolean;
+};
+
+type MicrophoneTest = {
+  peak: number;
+  rms: number;
+  silent: boolean;
+};
+
+type HistoryEntry = {
+  timestampMs: number;
+  transcript: string;
+  model: string;
+};
+
+type RuntimeStatus = {
+  phase: DictationPhase;
+  ready: boolean;
+};
+
+type ModelAvailability = {
+  model: ModelOption;
+  id: string;
+  cached: boolean;
+};
+
+type EnvironmentInfo = {
+  torchVersion: string | null;
+  cudaVersion: string | null;
+  cudaDevice: string | null;
+  device: string;
+  models: ModelAvailability[];
+};
+
+type DiagnosticCheck = {
+  name: string;
+  passed: boolean;
+  detail: string;
 };
 
 type LanguageOption = {
@@ -32,6 +221,40 @@ type LanguageOption = {
   label: string;
 };
 
+type ErrorCode =
+  | "pythonMissing"
+  | "dependencyMissing"
+  | "deviceNotFound"
+  | "transcriptEmpty"
+  | "injectionFailed"
+  | "shortcutInUse"
+  | "notReady"
+  | "cancelled"
+  | "unknown";
+
+type AppError = {
+  code: ErrorCode;
+  message: string;
+};
+
+const recoveryHints: Partial<Record<ErrorCode, string>> = {
+  pythonMissing: "Check the Python command under Runtime settings.",
+  dependencyMissing: "Run Diagnostics under Runtime settings to see what is missing.",
+  deviceNotFound: "Pick another input device under Audio settings.",
+};
+
+function isAppError(value: unknown): value is AppError {
+  return typeof value === "object" && value !== null && "code" in value && "message" in value;
+}
+
+function errorMessage(error: unknown): string {
+  if (isAppError(error)) {
+    const hint = recoveryHints[error.code];
+    return hint ? `${error.message}. ${hint}` : error.message;
+  }
+  return error instanceof Error ? error.message : String(error);
+}
+
 type IconProps = {
   className?: string;
 };
@@ -98,12 +321,104 @@ function IconStop({ className = "h-4 w-4" }: IconProps) {
 }
 
 const defaultSettings: AppSettings = {
+  version: 1,
   shortcut: "Ctrl+Shift+Space",
   recordingMode: "hold",
+  queueNextDictation: false,
   model: "qwen3Asr17b",
   language: "auto",
   pythonCommand: "python",
   inputDevice: "default",
+  outputDevice: "default",
+  fallbackDevices: [],
+  injectionDelayMs: 140,
+  outputMode: "injectAtCursor",
+  injectionMethod: "paste",
+  appProfiles: [],
+  silenceThreshold: 0.005,
+  quietEmptyTranscripts: true,
+  computeDevice: "auto",
+  audioCues: false,
+  trimThreshold: 0.02,
+  trimMarginMs: 200,
+  profiles: [],
+  activeProfile: null,
+  clipboardFallback: true,
+  recordingFormat: "pcm16",
+  focusGuard: false,
+  unfocusedGuard: true,
+  injectionDenylist: [
+    "1password",
+    "bitwarden",
+    "keepass",
+    "keepassxc",
+    "keychain access",
+    "loginwindow",
+    "lockapp",
+  ],
+  modelLanguages: {},
+  languagePinAfter: 5,
+  autoPinLanguage: false,
+  queueWhileBootstrapping: false,
+  venvPath: "",
+  useBundledPython: false,
+  modelCacheDir: null,
+  autoInstallDeps: false,
+  dependencyPins: [],
+  overlayHideDelayMs: 0,
+  removeFillers: false,
+  fillerWords: ["um", "uh", "uhm", "erm", "er", "ah", "hmm"],
+  applyVoiceCommands: false,
+  voiceCommands: [
+    { phrase: "new line", output: "\\n" },
+    { phrase: "new paragraph", output: "\\n\\n" },
+    { phrase: "period", output: "." },
+    { phrase: "full stop", output: "." },
+    { phrase: "comma", output: "," },
+    { phrase: "question mark", output: "?" },
+    { phrase: "exclamation mark", output: "!" },
+    { phrase: "colon", output: ":" },
+    { phrase: "semicolon", output: ";" },
+  ],
+  restoreClipboard: true,
+  keepRecordings: false,
+  saveHistory: true,
+  typeChunkSize: 0,
+  typeChunkDelayMs: 20,
+  mouseTrigger: null,
+  wakeWord: null,
+  captureSource: "microphone",
+  autoInject: true,
+  recorderRetries: 2,
+  captureSampleRate: null,
+  progressIntervalMs: 1000,
+  postProcessCommand: null,
+  overlayClickable: false,
+  overlayMonitor: "focusedWindow",
+  overlayMessages: {},
+  showOverlay: true,
+  startMinimized: false,
+  maxNewTokens: null,
+  beamSize: null,
+  temperature: null,
+  theme: "system",
+  appendSuffix: "",
+  pasteTemplate: null,
+  chunkLengthSecs: 90,
+  chunkOverlapSecs: 2,
+};
+
+const overlayMessageFields: Array<{ phase: DictationPhase; label: string; placeholder: string }> = [
+  { phase: "listening", label: "Listening", placeholder: "Listening" },
+  { phase: "transcribing", label: "Transcribing", placeholder: "Transcribing" },
+  { phase: "bootstrapping", label: "Preparing", placeholder: "Preparing" },
+  { phase: "idle", label: "Finished", placeholder: "{message}" },
+  { phase: "error", label: "Error", placeholder: "Error" },
+];
+
+const modelLabels: Record<ModelOption, string> = {
+  qwen3Asr17b: "Qwen3-ASR-1.7B",
+  qwen3Asr06b: "Qwen3-ASR-0.6B",
 };
 
 const modelDescriptions: Record<ModelOption, string> = {
@@ -179,6 +494,20 @@ function resolveLanguageInput(input: string): LanguageOption | undefined {
   });
 }
 
+const punctuationCodes = [
+  "Comma",
+  "Period",
+  "Semicolon",
+  "Quote",
+  "Backquote",
+  "BracketLeft",
+  "BracketRight",
+  "Backslash",
+  "Slash",
+  "Minus",
+  "Equal",
+];
+
 function shortcutKeyToken(event: React.KeyboardEvent<HTMLInputElement>): string | null {
   const key = event.key;
 
@@ -190,6 +519,16 @@ function shortcutKeyToken(event: React.KeyboardEvent<HTMLInputElement>): string
     return "Space";
   }
 
+  // Numpad and punctuation keys are matched by physical position so the
+  // token doesn't depend on the keyboard layout or NumLock state.
+  if (/^Numpad(\d|Enter|Add|Subtract|Multiply|Divide|Decimal|Equal)$/.test(event.code)) {
+    return event.code;
+  }
+
+  if (punctuationCodes.includes(event.code)) {
+    return event.code;
+  }
+
   if (key.length === 1) {
     if (/^[a-z]$/i.test(key)) {
       return key.toUpperCase();
@@ -225,6 +564,15 @@ function shortcutKeyToken(event: React.KeyboardEvent<HTMLInputElement>): string
   return mapped[key] ?? null;
 }
 
+const modifierTokens: Record<string, string> = {
+  Control: "Ctrl",
+  Shift: "Shift",
+  Alt: "Alt",
+  Meta: "Super",
+};
+
+const doubleTapWindowMs = 350;
+
 function buildShortcutCandidate(
   event: React.KeyboardEvent<HTMLInputElement>,
 ): string | null {
@@ -251,8 +599,44 @@ function buildShortcutCandidate(
   return parts.join("+");
 }
 
+function useDocumentTheme() {
+  useEffect(() => {
+    const apply = (theme: string) => {
+      document.documentElement.dataset.theme = theme;
+    };
+
+    const fromUrl = new URLSearchParams(window.location.search).get("theme");
+    if (fromUrl) {
+      apply(fromUrl);
+    }
+
+    invoke<string>("get_theme")
+      .then(apply)
+      .catch(() => {});
+
+    const unlisten = listen<string>("theme-changed", (event) => {
+      apply(event.payload);
+    });
+
+    return () => {
+      void unlisten.then((dispose) => dispose());
+    };
+  }, []);
+}
+
 function OverlayPill() {
   const [status, setStatus] = useState<DictationStatus>({ phase: "idle" });
+  const [progress, setProgress] = useState<TranscriptionProgress | null>(null);
+  const [blocked, setBlocked] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (blocked === null) {
+      return;
+    }
+
+    const timer = window.setTimeout(() => setBlocked(null), 1500);
+    return () => window.clearTimeout(timer);
+  }, [blocked]);
 
   useEffect(() => {
     let mounted = true;
@@ -261,12 +645,32 @@ function OverlayPill() {
     unlistenPromise = listen<DictationStatus>("dictation-state", (event) => {
       if (mounted) {
         setStatus(event.payload);
+        if (event.payload.phase !== "transcribing") {
+          setProgress(null);
+        }
+      }
+    });
+
+    const unlistenProgressPromise = listen<TranscriptionProgress>(
+      "transcription-progress",
+      (event) => {
+        if (mounted) {
+          setProgress(event.payload);
+        }
+      },
+    );
+
+    const unlistenBlockedPromise = listen<string>("dictation-blocked", (event) => {
+      if (mounted) {
+        setBlocked(event.payload);
       }
     });
 
     return () => {
       mounted = false;
       void unlistenPromise?.then((unlisten) => unlisten());
+      void unlistenProgressPromise.then((unlisten) => unlisten());
+      void unlistenBlockedPromise.then((unlisten) => unlisten());
     };
   }, []);
 
@@ -279,7 +683,7 @@ function OverlayPill() {
           ? "text-amber-400"
           : status.phase === "error"
             ? "text-rose-400"
-            : "text-slate-400";
+            : "text-ink-subtle";
 
   const dotColor =
     status.phase === "bootstrapping"
@@ -290,27 +694,53 @@ function OverlayPill() {
           ? "bg-amber-400"
           : status.phase === "error"
             ? "bg-rose-400"
-            : "bg-slate-400";
+            : "bg-ink-subtle";
 
   const label =
-    status.phase === "bootstrapping"
+    status.overlayText ??
+    (status.phase === "bootstrapping"
       ? "Preparing"
       : status.phase === "listening"
         ? "Listening"
         : status.phase === "transcribing"
-          ? "Transcribing"
+          ? progress?.stage === "loading"
+            ? "Loading model"
+            : progress?.stage === "decoding"
+              ? "Decoding"
+              : "Transcribing"
           : status.phase === "error"
             ? "Error"
-            : "Ready";
+            : (status.message ?? "Ready"));
+
+  // Only reachable when the overlay is clickable; otherwise clicks pass
+  // through the window.
+  function handleClick() {
+    if (status.phase === "listening") {
+      void invoke("stop_dictation");
+    } else if (status.phase === "transcribing") {
+      void invoke("abort_transcription");
+    }
+  }
 
   return (
     <main className="h-screen w-screen bg-transparent">
       <div className="flex h-full w-full items-center justify-center p-2">
-        <div className="overlay-pill flex items-center gap-3 rounded-full px-5 py-3 shadow-2xl">
+        <div
+          className="overlay-pill flex items-center gap-3 rounded-full px-5 py-3 shadow-2xl"
+          onClick={handleClick}
+        >
           <span className={`status-dot h-3 w-3 rounded-full ${dotColor}`} />
           <span className={`text-sm font-semibold tracking-wide ${statusColor}`}>
             {label}
           </span>
+          {blocked && status.phase !== "idle" && (
+            <span className="text-xs text-rose-300">{blocked}</span>
+          )}
+          {status.phase === "transcribing" && progress && (
+            <span className="text-xs tabular-nums text-ink-subtle">
+              {Math.floor(progress.elapsedMs / 1000)}s
+            </span>
+          )}
           {(status.phase === "listening" || status.phase === "transcribing") && (
             <div className="ml-1 flex items-end gap-1">
               <span className="scribble-wave h-2 w-1 rounded bg-cyan-400" />
@@ -324,26 +754,203 @@ function OverlayPill() {
   );
 }
 
+function ReviewPanel() {
+  const [text, setText] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let mounted = true;
+
+    void invoke<string | null>("get_pending_transcript").then((pending) => {
+      if (mounted && pending !== null) {
+        setText(pending);
+      }
+    });
+
+    const unlistenPromise = listen<string>("dictation-pending", (event) => {
+      if (mounted) {
+        setText(event.payload);
+        setError(null);
+      }
+    });
+
+    return () => {
+      mounted = false;
+      void unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  async function resolve(command: "confirm_injection" | "discard_transcript") {
+    setBusy(true);
+    setError(null);
+    try {
+      await invoke(command, command === "confirm_injection" ? { text } : {});
+    } catch (failure) {
+      setError(errorMessage(failure));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  return (
+    <main className="h-screen w-screen bg-transparent p-2">
+      <div className="overlay-pill flex h-full flex-col gap-3 rounded-2xl p-4 shadow-2xl">
+        <textarea
+          className="scribble-input flex-1 resize-none rounded-xl px-3 py-2 text-sm"
+          value={text}
+          onChange={(event) => setText(event.target.value)}
+          autoFocus
+        />
+        {error && <p className="text-xs text-rose-400">{error}</p>}
+        <div className="flex justify-end gap-2">
+          <button
+            type="button"
+            disabled={busy}
+            onClick={() => {
+              void resolve("discard_transcript");
+            }}
+            className="scribble-button scribble-button-secondary h-9 rounded-xl px-4 text-sm"
+          >
+            Discard
+          </button>
+          <button
+            type="button"
+            disabled={busy || text.trim().length === 0}
+            onClick={() => {
+              void resolve("confirm_injection");
+            }}
+            className="scribble-button h-9 rounded-xl px-4 text-sm"
+          >
+            Insert
+          </button>
+        </div>
+      </div>
+    </main>
+  );
+}
+
+function HistoryPanel() {
+  const [entries, setEntries] = useState<HistoryEntry[]>([]);
+  const [copiedId, setCopiedId] = useState<number | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let mounted = true;
+
+    const refresh = () => {
+      void invoke<HistoryEntry[]>("get_history", { limit: 200 })
+        .then((loaded) => {
+          if (mounted) {
+            setEntries(loaded);
+          }
+        })
+        .catch((failure) => {
+          if (mounted) {
+            setError(errorMessage(failure));
+          }
+        });
+    };
+
+    refresh();
+    const unlistenPromise = listen<string>("dictation-transcript", refresh);
+
+    return () => {
+      mounted = false;
+      void unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  async function copyEntry(id: number) {
+    setError(null);
+    try {
+      await invoke("copy_history_entry", { id });
+      setCopiedId(id);
+    } catch (failure) {
+      setError(errorMessage(failure));
+    }
+  }
+
+  return (
+    <main className="scribble-bg h-screen w-screen overflow-y-auto p-4 text-ink">
+      <h1 className="mb-3 text-lg font-semibold text-ink-accent">Transcript History</h1>
+      {error && <p className="mb-3 text-xs text-rose-400">{error}</p>}
+      {entries.length === 0 ? (
+        <p className="text-sm text-ink-faint">No transcripts yet.</p>
+      ) : (
+        <ul className="space-y-2">
+          {entries.map((entry) => (
+            <li
+              key={entry.timestampMs}
+              className="rounded-xl border border-[#00E5FF]/20 bg-panel/60 p-3"
+            >
+              <div className="mb-1 flex items-center gap-2 text-xs text-ink-faint">
+                <span>{new Date(entry.timestampMs).toLocaleString()}</span>
+                <span>· {entry.model}</span>
+                <button
+                  type="button"
+                  onClick={() => {
+                    void copyEntry(entry.timestampMs);
+                  }}
+                  className="ml-auto text-ink-accent underline-offset-2 hover:underline"
+                >
+                  {copiedId === entry.timestampMs ? "Copied" : "Copy"}
+                </button>
+              </div>
+              <p className="whitespace-pre-wrap text-sm text-ink-soft">{entry.transcript}</p>
+            </li>
+          ))}
+        </ul>
+      )}
+    </main>
+  );
+}
+
 function SettingsPage() {
   const [settings, setSettings] = useState<AppSettings>(defaultSettings);
   const [inputDevices, setInputDevices] = useState<string[]>(["default"]);
+  const [inputDeviceMissing, setInputDeviceMissing] = useState(false);
+  const [outputDevices, setOutputDevices] = useState<string[]>(["default"]);
   const [status, setStatus] = useState<DictationStatus>({
     phase: "idle",
     message: "Ready",
   });
+  const [bootstrapProgress, setBootstrapProgress] = useState<BootstrapProgress | null>(null);
   const [saving, setSaving] = useState(false);
   const [activeTab, setActiveTab] = useState<SettingsTab>("general");
   const [languageQuery, setLanguageQuery] = useState(formatLanguageLabel("auto"));
   const [languageMenuOpen, setLanguageMenuOpen] = useState(false);
   const [capturingShortcut, setCapturingShortcut] = useState(false);
+  const [detectedLanguage, setDetectedLanguage] = useState<string | null>(null);
+  const [languageSuggestion, setLanguageSuggestion] = useState<LanguageSuggestion | null>(null);
+  const [diagnostics, setDiagnostics] = useState<DiagnosticCheck[]>([]);
+  const [diagnosing, setDiagnosing] = useState(false);
+  const [profileName, setProfileName] = useState("");
+  const comboPressed = useRef(false);
+  const lastModifierTap = useRef<{ token: string; at: number } | null>(null);
+  const [environment, setEnvironment] = useState<EnvironmentInfo | null>(null);
+  const [selectedDevice, setSelectedDevice] = useState<SelectedInputDevice | null>(null);
+  const [deviceDetails, setDeviceDetails] = useState<InputDeviceInfo[]>([]);
+  const [micTest, setMicTest] = useState<MicrophoneTest | null>(null);
+  const [testingMic, setTestingMic] = useState(false);
+  const [recordingsUsage, setRecordingsUsage] = useState<RecordingsUsage | null>(null);
+  const [activeBinding, setActiveBinding] = useState<RegisteredShortcut | null>(null);
+  const [queryingEnvironment, setQueryingEnvironment] = useState(false);
+  const [benchmarkPath, setBenchmarkPath] = useState("");
+  const [benchmarks, setBenchmarks] = useState<ModelBenchmark[] | null>(null);
+  const [benchmarking, setBenchmarking] = useState(false);
 
   useEffect(() => {
     void (async () => {
-      const [loaded, devices] = await Promise.all([
+      const [loaded, devices, runtime] = await Promise.all([
         invoke<AppSettings>("get_settings"),
         invoke<string[]>("list_input_devices"),
+        invoke<RuntimeStatus>("get_dictation_status"),
       ]);
       setSettings(loaded);
+      setStatus((previous) =>
+        previous.phase === runtime.phase ? previous : { phase: runtime.phase, message: null },
+      );
       setLanguageQuery(formatLanguageLabel(loaded.language));
 
       const normalized = devices.length > 0 ? devices : ["default"];
@@ -353,18 +960,69 @@ function SettingsPage() {
       setInputDevices(normalized);
     })();
 
+    const checkInputDevice = () => {
+      void invoke<boolean>("validate_input_device")
+        .then((present) => setInputDeviceMissing(!present))
+        .catch(() => setInputDeviceMissing(false));
+    };
+    checkInputDevice();
+
+    void invoke<string[]>("list_output_devices")
+      .then((devices) => setOutputDevices(devices.length > 0 ? devices : ["default"]))
+      .catch(() => setOutputDevices(["default"]));
+
+    void invoke<InputDeviceInfo[]>("list_input_device_details")
+      .then(setDeviceDetails)
+      .catch(() => setDeviceDetails([]));
+
     let mounted = true;
     let unlistenPromise: Promise<() => void> | undefined;
 
     unlistenPromise = listen<DictationStatus>("dictation-state", (event) => {
       if (mounted) {
         setStatus(event.payload);
+        const stage = event.payload.stage;
+        if (stage) {
+          setBootstrapProgress((previous) => ({
+            stage,
+            reached: stage === "failed" ? (previous?.reached ?? "checkingPython") : stage,
+          }));
+        }
+      }
+    });
+    const unlistenLanguagePromise = listen<string>("dictation-language", (event) => {
+      if (mounted) {
+        setDetectedLanguage(event.payload);
+      }
+    });
+    const unlistenSuggestionPromise = listen<LanguageSuggestion>("language-pin-suggested", (event) => {
+      if (!mounted) {
+        return;
+      }
+      setLanguageSuggestion(event.payload);
+      if (event.payload.pinned) {
+        applyPinnedLanguage(event.payload.language);
+      }
+    });
+    const unlistenDevicePromise = listen<SelectedInputDevice>("dictation-input-device", (event) => {
+      if (mounted) {
+        setSelectedDevice(event.payload);
+      }
+    });
+    const unlistenDevicesPromise = listen<string[]>("input-devices-changed", (event) => {
+      if (mounted) {
+        setInputDevices(event.payload.length > 0 ? event.payload : ["default"]);
+        checkInputDevice();
       }
     });
 
     return () => {
       mounted = false;
       void unlistenPromise?.then((unlisten) => unlisten());
+      void unlistenLanguagePromise.then((unlisten) => unlisten());
+      void unlistenSuggestionPromise.then((unlisten) => unlisten());
+      void unlistenDevicePromise.then((unlisten) => unlisten());
+      void unlistenDevicesPromise.then((unlisten) => unlisten());
     };
   }, []);
 
@@ -397,9 +1055,32 @@ function SettingsPage() {
     if (status.phase === "error") {
       return "text-rose-400 border-rose-400/30 bg-rose-400/10";
     }
-    return "text-slate-400 border-slate-400/30 bg-slate-400/10";
+    return "text-ink-subtle border-ink-subtle/30 bg-ink-subtle/10";
   }, [status.phase]);
 
+  // Only the language fields change, so other unsaved edits survive.
+  function applyPinnedLanguage(language: string) {
+    setSettings((previous) => ({
+      ...previous,
+      language,
+      modelLanguages: { ...previous.modelLanguages, [previous.model]: language },
+    }));
+    setLanguageQuery(formatLanguageLabel(language));
+  }
+
+  async function pinSuggestedLanguage(language: string) {
+    try {
+      const saved = await invoke<AppSettings>("pin_language", { language });
+      applyPinnedLanguage(saved.language);
+      setLanguageSuggestion(null);
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
   function chooseLanguage(code: string) {
     setSettings((previous) => ({ ...previous, language: code }));
     setLanguageQuery(formatLanguageLabel(code));
@@ -417,6 +1098,38 @@ function SettingsPage() {
       return;
     }
 
+    comboPressed.current = true;
+    await applyShortcutCandidate(candidate);
+  }
+
+  async function captureModifierRelease(
+    event: React.KeyboardEvent<HTMLInputElement>,
+  ): Promise<void> {
+    const token = modifierTokens[event.key];
+    if (!token) {
+      return;
+    }
+
+    event.preventDefault();
+    if (event.ctrlKey || event.shiftKey || event.altKey || event.metaKey) {
+      return;
+    }
+
+    if (comboPressed.current) {
+      comboPressed.current = false;
+      return;
+    }
+
+    const now = Date.now();
+    const previous = lastModifierTap.current;
+    const isDoubleTap =
+      previous !== null && previous.token === token && now - previous.at <= doubleTapWindowMs;
+    lastModifierTap.current = isDoubleTap ? null : { token, at: now };
+
+    await applyShortcutCandidate(isDoubleTap ? `Double+${token}` : token);
+  }
+
+  async function applyShortcutCandidate(candidate: string): Promise<void> {
     try {
       const normalized = await invoke<string>("normalize_shortcut", {
         shortcut: candidate,
@@ -426,85 +1139,363 @@ function SettingsPage() {
     } catch (error) {
       setStatus({
         phase: "error",
-        message: error instanceof Error ? error.message : String(error),
+        message: errorMessage(error),
       });
     }
   }
 
-  async function persistSettings() {
-    setSaving(true);
+  async function refreshActiveBinding() {
     try {
-      const updated = await invoke<AppSettings>("update_settings", { settings });
+      setActiveBinding(await invoke<RegisteredShortcut>("get_registered_shortcut"));
+    } catch {
+      setActiveBinding(null);
+    }
+  }
+
+  useEffect(() => {
+    void refreshActiveBinding();
+  }, [settings.shortcut, settings.recordingMode, settings.activeProfile]);
+
+  async function reregisterShortcut() {
+    try {
+      const registered = await invoke<string>("reregister_shortcut");
+      setStatus({ phase: "idle", message: `Shortcut ${registered} re-registered` });
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  function updateAppProfile(index: number, patch: Partial<AppInjectionProfile>) {
+    setSettings((previous) => ({
+      ...previous,
+      appProfiles: previous.appProfiles.map((profile, position) =>
+        position === index ? { ...profile, ...patch } : profile,
+      ),
+    }));
+  }
+
+  function updateVoiceCommand(index: number, patch: Partial<VoiceCommand>) {
+    setSettings((previous) => ({
+      ...previous,
+      voiceCommands: previous.voiceCommands.map((command, position) =>
+        position === index ? { ...command, ...patch } : command,
+      ),
+    }));
+  }
+
+  function saveCurrentAsProfile() {
+    const name = profileName.trim();
+    if (!name) {
+      return;
+    }
+
+    setSettings((previous) => {
+      const profile = {
+        name,
+        shortcut: previous.shortcut,
+        recordingMode: previous.recordingMode,
+      };
+      const existing = previous.profiles.findIndex((candidate) => candidate.name === name);
+      const profiles =
+        existing >= 0
+          ? previous.profiles.map((candidate, index) => (index === existing ? profile : candidate))
+          : [...previous.profiles, profile];
+      return {
+        ...previous,
+        profiles,
+        activeProfile: existing >= 0 ? existing : profiles.length - 1,
+      };
+    });
+    setProfileName("");
+  }
+
+  async function switchProfile(name: string) {
+    try {
+      const updated = await invoke<AppSettings>("switch_profile", { name });
       setSettings(updated);
-      setLanguageQuery(formatLanguageLabel(updated.language));
-      setStatus({ phase: "idle", message: "Settings saved" });
+      setStatus({ phase: "idle", message: `Switched to profile ${name}` });
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function queryEnvironment() {
+    setQueryingEnvironment(true);
+    try {
+      setEnvironment(await invoke<EnvironmentInfo>("query_environment"));
     } catch (error) {
       setStatus({
         phase: "error",
-        message: error instanceof Error ? error.message : String(error),
+        message: errorMessage(error),
       });
     } finally {
-      setSaving(false);
+      setQueryingEnvironment(false);
     }
   }
 
-  async function toggleDictation() {
+  async function runDiagnostics() {
+    setDiagnosing(true);
     try {
-      await invoke("toggle_dictation");
+      setDiagnostics(await invoke<DiagnosticCheck[]>("run_diagnostics"));
     } catch (error) {
       setStatus({
         phase: "error",
-        message: error instanceof Error ? error.message : String(error),
+        message: errorMessage(error),
       });
+    } finally {
+      setDiagnosing(false);
     }
   }
 
-  return (
-    <main className="scribble-bg h-screen w-screen overflow-hidden text-[#e0f4ff]">
-      <div className="pointer-events-none absolute inset-0 overflow-hidden">
-        <svg
-          className="floating-scribble absolute left-10 top-10 h-32 w-32 opacity-10"
-          viewBox="0 0 100 100"
-        >
-          <path
-            d="M10,50 Q25,20 50,50 T90,50"
-            stroke="#00E5FF"
-            strokeWidth="2"
-            fill="none"
-          />
-        </svg>
-        <svg
-          className="floating-scribble absolute bottom-20 right-20 h-40 w-40 opacity-10"
-          style={{ animationDelay: "2s" }}
-          viewBox="0 0 100 100"
-        >
-          <path
-            d="M20,80 Q50,20 80,80"
-            stroke="#2962FF"
-            strokeWidth="2"
-            fill="none"
+  async function testInjection() {
+    try {
+      await invoke("test_injection", { text: null });
+      setStatus({ phase: "idle", message: "Test text will be injected in 3 seconds" });
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function warmupModel() {
+    try {
+      await invoke("warmup");
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function retryBootstrap() {
+    try {
+      await invoke("retry_bootstrap");
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function refreshRecordingsUsage() {
+    try {
+      setRecordingsUsage(await invoke<RecordingsUsage>("get_recordings_usage"));
+    } catch {
+      setRecordingsUsage(null);
+    }
+  }
+
+  async function pruneRecordings(options: { keepLast?: number; olderThanDays?: number }) {
+    try {
+      const removed = await invoke<number>("prune_recordings", options);
+      setStatus({
+        phase: "idle",
+        message: `Removed ${removed} recording${removed === 1 ? "" : "s"}`,
+      });
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    } finally {
+      void refreshRecordingsUsage();
+    }
+  }
+
+  useEffect(() => {
+    if (settings.keepRecordings) {
+      void refreshRecordingsUsage();
+    }
+  }, [settings.keepRecordings]);
+
+  async function testMicrophone() {
+    setTestingMic(true);
+    setMicTest(null);
+    try {
+      setMicTest(await invoke<MicrophoneTest>("test_microphone", { seconds: 3 }));
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    } finally {
+      setTestingMic(false);
+    }
+  }
+
+  async function benchmarkModels() {
+    setBenchmarking(true);
+    setBenchmarks(null);
+    try {
+      setBenchmarks(
+        await invoke<ModelBenchmark[]>("benchmark_models", { samplePath: benchmarkPath.trim() }),
+      );
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    } finally {
+      setBenchmarking(false);
+    }
+  }
+
+  async function openHistory() {
+    try {
+      await invoke("open_history");
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function openRecordingsFolder() {
+    try {
+      await invoke("open_recordings_folder");
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function persistSettings() {
+    setSaving(true);
+    try {
+      const updated = await invoke<AppSettings>("update_settings", { settings });
+      setSettings(updated);
+      setLanguageQuery(formatLanguageLabel(updated.language));
+      setInputDeviceMissing(!(await invoke<boolean>("validate_input_device")));
+      setStatus({ phase: "idle", message: "Settings saved" });
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    } finally {
+      setSaving(false);
+    }
+  }
+
+  async function resetSettings() {
+    if (!window.confirm("Reset all settings to their defaults?")) {
+      return;
+    }
+
+    setSaving(true);
+    try {
+      const restored = await invoke<AppSettings>("reset_settings");
+      setSettings(restored);
+      setLanguageQuery(formatLanguageLabel(restored.language));
+      setInputDeviceMissing(!(await invoke<boolean>("validate_input_device")));
+      setStatus({ phase: "idle", message: "Settings reset to defaults" });
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    } finally {
+      setSaving(false);
+    }
+  }
+
+  async function quitApp() {
+    const busy = status.phase === "listening" || status.phase === "transcribing";
+    if (busy && !window.confirm("A dictation is in progress and will be discarded. Quit anyway?")) {
+      return;
+    }
+
+    try {
+      await invoke("quit_app");
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function abortTranscription() {
+    try {
+      await invoke("abort_transcription");
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  async function toggleDictation() {
+    try {
+      await invoke("toggle_dictation");
+    } catch (error) {
+      setStatus({
+        phase: "error",
+        message: errorMessage(error),
+      });
+    }
+  }
+
+  return (
+    <main className="scribble-bg h-screen w-screen overflow-hidden text-ink">
+      <div className="pointer-events-none absolute inset-0 overflow-hidden">
+        <svg
+          className="floating-scribble absolute left-10 top-10 h-32 w-32 opacity-10"
+          viewBox="0 0 100 100"
+        >
+          <path
+            d="M10,50 Q25,20 50,50 T90,50"
+            stroke="#00E5FF"
+            strokeWidth="2"
+            fill="none"
+          />
+        </svg>
+        <svg
+          className="floating-scribble absolute bottom-20 right-20 h-40 w-40 opacity-10"
+          style={{ animationDelay: "2s" }}
+          viewBox="0 0 100 100"
+        >
+          <path
+            d="M20,80 Q50,20 80,80"
+            stroke="#2962FF"
+            strokeWidth="2"
+            fill="none"
           />
         </svg>
       </div>
 
       <div className="relative z-10 flex h-full flex-col">
-        <header className="flex items-center gap-4 border-b border-[#00E5FF]/20 bg-[#060d18]/70 px-6 py-4">
+        <header className="flex items-center gap-4 border-b border-[#00E5FF]/20 bg-panel/70 px-6 py-4">
           <img src="/delulu-talks-icon.svg" alt="Delulu Talks" className="h-12 w-12" />
           <div className="min-w-0">
             <h1 className="bg-gradient-to-r from-[#00E5FF] to-[#2962FF] bg-clip-text text-xl font-bold text-transparent">
               Delulu Talks
             </h1>
-            <p className="text-xs text-slate-400">
+            <p className="text-xs text-ink-subtle">
               Chaotic speech-to-text assistant running in tray
             </p>
           </div>
-          <div className="ml-auto rounded-full border border-[#00E5FF]/30 bg-[#00E5FF]/10 px-3 py-1 text-xs text-[#7befff]">
+          <div className="ml-auto rounded-full border border-[#00E5FF]/30 bg-[#00E5FF]/10 px-3 py-1 text-xs text-ink-accent">
             {tabs.find((tab) => tab.id === activeTab)?.label}
           </div>
         </header>
 
         <div className="flex min-h-0 flex-1">
-          <aside className="w-60 border-r border-[#00E5FF]/20 bg-[#060d18]/45 p-4">
+          <aside className="w-60 border-r border-[#00E5FF]/20 bg-panel/45 p-4">
             <nav className="space-y-2">
               {tabs.map((tab) => {
                 const isActive = activeTab === tab.id;
@@ -516,8 +1507,8 @@ function SettingsPage() {
                     onClick={() => setActiveTab(tab.id)}
                     className={`w-full rounded-xl px-4 py-3 text-left transition ${
                       isActive
-                        ? "scribble-border-active text-[#00E5FF]"
-                        : "border border-[#00E5FF]/15 text-slate-400 hover:border-[#00E5FF]/30 hover:text-slate-200"
+                        ? "scribble-border-active text-ink-brand"
+                        : "border border-[#00E5FF]/15 text-ink-subtle hover:border-[#00E5FF]/30 hover:text-ink-soft"
                     }`}
                   >
                     <div className="flex items-center gap-2">
@@ -532,7 +1523,7 @@ function SettingsPage() {
 
             <div className={`mt-4 rounded-xl border px-3 py-2 text-xs ${statusColor}`}>
               <p className="uppercase tracking-wide">{status.phase}</p>
-              <p className="mt-1 text-slate-300">{status.message ?? "Ready"}</p>
+              <p className="mt-1 text-ink-muted">{status.message ?? "Ready"}</p>
             </div>
           </aside>
 
@@ -541,11 +1532,11 @@ function SettingsPage() {
               <div className="mx-auto max-w-2xl space-y-6">
                 {activeTab === "general" && (
                   <div className="scribble-border scribble-glow rounded-2xl p-6">
-                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-[#00E5FF]"><IconGeneral className="h-5 w-5" />General</h2>
+                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-ink-brand"><IconGeneral className="h-5 w-5" />General</h2>
 
                     <div className="space-y-5">
                       <div className="grid gap-2">
-                        <label className="text-sm font-medium text-slate-300">
+                        <label className="text-sm font-medium text-ink-muted">
                           Shortcut Recorder
                         </label>
                         <input
@@ -557,19 +1548,103 @@ function SettingsPage() {
                           onKeyDown={(event) => {
                             void captureShortcut(event);
                           }}
+                          onKeyUp={(event) => {
+                            void captureModifierRelease(event);
+                          }}
                         />
-                        <p className="text-xs text-slate-500">
+                        <p className="text-xs text-ink-faint">
                           {capturingShortcut
                             ? "Press any key or combo now. Single key works too."
                             : "Click the field, then press your shortcut."}
                         </p>
-                        <p className="text-xs text-slate-500">
+                        <p className="text-xs text-ink-faint">
                           Hold mode supports one-key push-to-talk: press starts, release stops.
                         </p>
+                        <p className="text-xs text-ink-faint">
+                          Tap a lone modifier, or double-tap it for Double+Ctrl style triggers (Windows and macOS only).
+                        </p>
+                        <button
+                          type="button"
+                          onClick={() => {
+                            void reregisterShortcut();
+                          }}
+                          className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline"
+                        >
+                          Shortcut stopped responding? Re-register it
+                        </button>
+                        {activeBinding && (
+                          <p className="text-xs text-ink-subtle">
+                            Active binding: <span className="font-mono text-ink-accent">{activeBinding.shortcut}</span> ({activeBinding.recordingMode === "hold" ? "hold" : "toggle"})
+                          </p>
+                        )}
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Shortcut Profiles
+                        </label>
+                        {settings.profiles.length > 0 && (
+                          <div className="flex flex-wrap gap-2">
+                            {settings.profiles.map((profile, index) => (
+                              <div key={profile.name} className="flex items-center gap-1">
+                                <button
+                                  type="button"
+                                  onClick={() => {
+                                    void switchProfile(profile.name);
+                                  }}
+                                  className={`rounded-xl border px-3 py-2 text-xs transition ${
+                                    settings.activeProfile === index
+                                      ? "scribble-border-active bg-[#00E5FF]/10 text-ink-brand"
+                                      : "border-[#00E5FF]/20 text-ink-subtle hover:border-[#00E5FF]/40"
+                                  }`}
+                                >
+                                  {profile.name} · {profile.shortcut} · {profile.recordingMode}
+                                </button>
+                                <button
+                                  type="button"
+                                  aria-label={`Remove ${profile.name}`}
+                                  onClick={() =>
+                                    setSettings((previous) => ({
+                                      ...previous,
+                                      profiles: previous.profiles.filter((_, position) => position !== index),
+                                      activeProfile:
+                                        previous.activeProfile === null || previous.activeProfile === index
+                                          ? null
+                                          : previous.activeProfile > index
+                                            ? previous.activeProfile - 1
+                                            : previous.activeProfile,
+                                    }))
+                                  }
+                                  className="px-1 text-xs text-ink-faint hover:text-rose-400"
+                                >
+                                  ×
+                                </button>
+                              </div>
+                            ))}
+                          </div>
+                        )}
+                        <div className="flex gap-2">
+                          <input
+                            className="scribble-input h-10 flex-1 rounded-xl px-3 text-sm"
+                            value={profileName}
+                            placeholder="Profile name, e.g. Laptop"
+                            onChange={(event) => setProfileName(event.target.value)}
+                          />
+                          <button
+                            type="button"
+                            onClick={saveCurrentAsProfile}
+                            className="rounded-xl border border-[#00E5FF]/20 px-3 text-xs text-ink-muted hover:border-[#00E5FF]/40"
+                          >
+                            Save current as profile
+                          </button>
+                        </div>
+                        <p className="text-xs text-ink-faint">
+                          Switching applies immediately. Save Settings to keep new or removed profiles.
+                        </p>
                       </div>
 
                       <div className="grid gap-2">
-                        <label className="text-sm font-medium text-slate-300">
+                        <label className="text-sm font-medium text-ink-muted">
                           Recording Mode
                         </label>
                         <div className="grid gap-2 sm:grid-cols-2">
@@ -583,8 +1658,8 @@ function SettingsPage() {
                             }
                             className={`rounded-xl border px-4 py-3 text-left text-sm transition ${
                               settings.recordingMode === "hold"
-                                ? "scribble-border-active bg-[#00E5FF]/10 text-[#00E5FF]"
-                                : "border-[#00E5FF]/20 text-slate-400 hover:border-[#00E5FF]/40"
+                                ? "scribble-border-active bg-[#00E5FF]/10 text-ink-brand"
+                                : "border-[#00E5FF]/20 text-ink-subtle hover:border-[#00E5FF]/40"
                             }`}
                           >
                             Hold-to-talk (default)
@@ -599,91 +1674,767 @@ function SettingsPage() {
                             }
                             className={`rounded-xl border px-4 py-3 text-left text-sm transition ${
                               settings.recordingMode === "toggle"
-                                ? "scribble-border-active bg-[#00E5FF]/10 text-[#00E5FF]"
-                                : "border-[#00E5FF]/20 text-slate-400 hover:border-[#00E5FF]/40"
+                                ? "scribble-border-active bg-[#00E5FF]/10 text-ink-brand"
+                                : "border-[#00E5FF]/20 text-ink-subtle hover:border-[#00E5FF]/40"
                             }`}
                           >
                             Toggle-to-record
                           </button>
                         </div>
+                        <label className="mt-1 flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.queueNextDictation}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                queueNextDictation: event.target.checked,
+                              }))
+                            }
+                          />
+                          Queue a new dictation started while the last one is still transcribing
+                        </label>
                       </div>
-                    </div>
-                  </div>
-                )}
-
-                {activeTab === "speech" && (
-                  <div className="scribble-border scribble-glow rounded-2xl p-6">
-                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-[#00E5FF]"><IconSpeech className="h-5 w-5" />Speech</h2>
 
-                    <div className="space-y-5">
                       <div className="grid gap-2">
-                        <label className="text-sm font-medium text-slate-300">ASR Model</label>
+                        <label className="text-sm font-medium text-ink-muted">
+                          Mouse Trigger
+                        </label>
                         <select
                           className="scribble-input h-11 rounded-xl px-4 text-sm"
-                          value={settings.model}
+                          value={settings.mouseTrigger ?? ""}
                           onChange={(event) =>
                             setSettings((previous) => ({
                               ...previous,
-                              model: event.target.value as ModelOption,
+                              mouseTrigger: (event.target.value || null) as MouseButton | null,
                             }))
                           }
                         >
-                          <option value="qwen3Asr17b">Qwen3-ASR-1.7B</option>
-                          <option value="qwen3Asr06b">Qwen3-ASR-0.6B</option>
+                          <option value="">None</option>
+                          <option value="middle">Middle button</option>
+                          <option value="back">Back thumb button</option>
+                          <option value="forward">Forward thumb button</option>
                         </select>
-                        <p className="text-xs text-slate-500">
-                          {modelDescriptions[settings.model]}
+                        <p className="text-xs text-ink-faint">
+                          Works like the shortcut and follows the recording mode. Windows and macOS only; the click still reaches the app under the cursor.
                         </p>
                       </div>
 
                       <div className="grid gap-2">
-                        <label className="text-sm font-medium text-slate-300">
-                          Language (searchable)
+                        <label className="text-sm font-medium text-ink-muted">
+                          Wake Word
                         </label>
-                        <div className="relative">
-                          <input
-                            className="scribble-input h-11 w-full rounded-xl px-4 text-sm"
-                            value={languageQuery}
-                            onFocus={() => setLanguageMenuOpen(true)}
-                            onChange={(event) => {
-                              setLanguageQuery(event.target.value);
-                              setLanguageMenuOpen(true);
-                            }}
-                            onBlur={() => {
-                              window.setTimeout(() => {
-                                const resolved = resolveLanguageInput(languageQuery);
-                                if (resolved) {
-                                  chooseLanguage(resolved.code);
-                                } else {
-                                  setLanguageQuery(formatLanguageLabel(settings.language));
-                                }
-                                setLanguageMenuOpen(false);
-                              }, 120);
-                            }}
-                            onKeyDown={(event) => {
-                              if (event.key === "Enter") {
-                                event.preventDefault();
-                                const resolved = resolveLanguageInput(languageQuery);
-                                if (resolved) {
-                                  chooseLanguage(resolved.code);
-                                } else if (filteredLanguages[0]) {
-                                  chooseLanguage(filteredLanguages[0].code);
-                                }
-                              }
-
-                              if (event.key === "Escape") {
-                                setLanguageQuery(formatLanguageLabel(settings.language));
-                                setLanguageMenuOpen(false);
-                              }
-                            }}
-                            placeholder="Search language name or code"
-                          />
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.wakeWord ?? ""}
+                          placeholder="Off"
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              wakeWord: event.target.value || null,
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Say this phrase to start dictating hands-free; recording stops after a short pause. Keeps the microphone open and runs the 0.6B model whenever it hears sound, so leave it empty to turn it off.
+                        </p>
+                      </div>
 
-                          {languageMenuOpen && (
-                            <div className="scribble-border absolute z-20 mt-1 max-h-56 w-full overflow-y-auto rounded-xl border bg-[#081224] p-1">
-                              {filteredLanguages.slice(0, 20).map((language) => (
-                                <button
-                                  key={language.code}
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Transcript Output
+                        </label>
+                        <select
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.outputMode}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              outputMode: event.target.value as OutputMode,
+                            }))
+                          }
+                        >
+                          <option value="injectAtCursor">Paste at cursor</option>
+                          <option value="clipboardOnly">Copy to clipboard only</option>
+                          <option value="both">Paste and keep on clipboard</option>
+                          <option value="confirmBeforeInject">Review before pasting</option>
+                        </select>
+                        <p className="text-xs text-ink-faint">
+                          Clipboard-only avoids pasting into the wrong window if focus changes while transcribing.
+                        </p>
+                        <label className="flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.clipboardFallback}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                clipboardFallback: event.target.checked,
+                              }))
+                            }
+                          />
+                          Leave the transcript on the clipboard if inserting it fails
+                        </label>
+                        <label className="flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.restoreClipboard}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                restoreClipboard: event.target.checked,
+                              }))
+                            }
+                          />
+                          Restore my previous clipboard after pasting (when off, the transcript stays on the clipboard)
+                        </label>
+                        <label className="flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.focusGuard}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                focusGuard: event.target.checked,
+                              }))
+                            }
+                          />
+                          Copy instead of pasting if focus moved to another app while dictating
+                        </label>
+                        <label className="flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.unfocusedGuard}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                unfocusedGuard: event.target.checked,
+                              }))
+                            }
+                          />
+                          Copy instead of pasting if no window is focused, e.g. after clicking the desktop
+                        </label>
+                      </div>
+
+                      <label className="flex items-center gap-3 text-sm text-ink-muted">
+                        <input
+                          type="checkbox"
+                          checked={settings.autoInject}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              autoInject: event.target.checked,
+                            }))
+                          }
+                        />
+                        Paste or type transcripts automatically (turn off for dry runs)
+                      </label>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Injection Method
+                        </label>
+                        <select
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.injectionMethod}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              injectionMethod: event.target.value as InjectionMethod,
+                            }))
+                          }
+                        >
+                          <option value="paste">Paste via clipboard</option>
+                          <option value="typing">Type characters</option>
+                        </select>
+                        <p className="text-xs text-ink-faint">
+                          Typing is slower but works in apps that drop emoji or CJK on paste.
+                        </p>
+                        {settings.injectionMethod === "typing" && (
+                          <div className="grid grid-cols-2 gap-2">
+                            <input
+                              type="number"
+                              min={0}
+                              step={8}
+                              className="scribble-input h-10 rounded-xl px-3 text-sm"
+                              value={settings.typeChunkSize}
+                              onChange={(event) =>
+                                setSettings((previous) => ({
+                                  ...previous,
+                                  typeChunkSize: Math.max(0, Number(event.target.value) || 0),
+                                }))
+                              }
+                              aria-label="Characters per chunk"
+                            />
+                            <input
+                              type="number"
+                              min={0}
+                              max={1000}
+                              step={5}
+                              className="scribble-input h-10 rounded-xl px-3 text-sm"
+                              value={settings.typeChunkDelayMs}
+                              onChange={(event) =>
+                                setSettings((previous) => ({
+                                  ...previous,
+                                  typeChunkDelayMs: Math.max(0, Number(event.target.value) || 0),
+                                }))
+                              }
+                              aria-label="Delay between chunks (ms)"
+                            />
+                            <p className="col-span-2 text-xs text-ink-faint">
+                              Characters per chunk and the pause between chunks (ms). Set chunk size to 0 to type everything at once; lower it if an editor drops characters.
+                            </p>
+                          </div>
+                        )}
+                        <button
+                          type="button"
+                          onClick={() => {
+                            void testInjection();
+                          }}
+                          className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline"
+                        >
+                          Test injection (focus the target app within 3 seconds)
+                        </button>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Paste Delay (ms)
+                        </label>
+                        <input
+                          type="number"
+                          min={0}
+                          max={2000}
+                          step={10}
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.injectionDelayMs}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              injectionDelayMs: Math.max(0, Number(event.target.value) || 0),
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          How long to wait after pasting before restoring your clipboard. Raise it if text lands in the wrong place.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Per-App Overrides
+                        </label>
+                        {settings.appProfiles.map((profile, index) => (
+                          <div key={index} className="grid grid-cols-[1fr_auto_6rem_auto] gap-2">
+                            <input
+                              className="scribble-input h-10 rounded-xl px-3 text-sm"
+                              value={profile.app}
+                              placeholder="slack.exe"
+                              onChange={(event) => updateAppProfile(index, { app: event.target.value })}
+                            />
+                            <select
+                              className="scribble-input h-10 rounded-xl px-3 text-sm"
+                              value={profile.injectionMethod}
+                              onChange={(event) =>
+                                updateAppProfile(index, {
+                                  injectionMethod: event.target.value as InjectionMethod,
+                                })
+                              }
+                            >
+                              <option value="paste">Paste</option>
+                              <option value="typing">Type</option>
+                            </select>
+                            <input
+                              type="number"
+                              min={0}
+                              max={2000}
+                              step={10}
+                              className="scribble-input h-10 rounded-xl px-3 text-sm"
+                              value={profile.injectionDelayMs}
+                              onChange={(event) =>
+                                updateAppProfile(index, {
+                                  injectionDelayMs: Math.max(0, Number(event.target.value) || 0),
+                                })
+                              }
+                            />
+                            <button
+                              type="button"
+                              onClick={() =>
+                                setSettings((previous) => ({
+                                  ...previous,
+                                  appProfiles: previous.appProfiles.filter((_, position) => position !== index),
+                                }))
+                              }
+                              className="rounded-xl border border-[#00E5FF]/20 px-3 text-xs text-ink-subtle hover:border-[#00E5FF]/40"
+                            >
+                              Remove
+                            </button>
+                          </div>
+                        ))}
+                        <button
+                          type="button"
+                          onClick={() =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              appProfiles: [
+                                ...previous.appProfiles,
+                                {
+                                  app: "",
+                                  injectionMethod: previous.injectionMethod,
+                                  injectionDelayMs: previous.injectionDelayMs,
+                                },
+                              ],
+                            }))
+                          }
+                          className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline"
+                        >
+                          Add app override
+                        </button>
+                        <p className="text-xs text-ink-faint">
+                          Matched against the focused app's process name when the transcript is inserted.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Never Paste Into
+                        </label>
+                        <textarea
+                          rows={4}
+                          className="scribble-input rounded-xl px-4 py-3 text-sm"
+                          value={settings.injectionDenylist.join("\n")}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              injectionDenylist: event.target.value.split("\n"),
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          One app per line, such as password managers and lock screens. The transcript goes to the clipboard instead.
+                        </p>
+                      </div>
+                    </div>
+                  </div>
+                )}
+
+                {activeTab === "speech" && (
+                  <div className="scribble-border scribble-glow rounded-2xl p-6">
+                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-ink-brand"><IconSpeech className="h-5 w-5" />Speech</h2>
+
+                    <div className="space-y-5">
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">ASR Model</label>
+                        <select
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.model}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              model: event.target.value as ModelOption,
+                            }))
+                          }
+                        >
+                          <option value="qwen3Asr17b">{modelLabels.qwen3Asr17b}</option>
+                          <option value="qwen3Asr06b">{modelLabels.qwen3Asr06b}</option>
+                        </select>
+                        <p className="text-xs text-ink-faint">
+                          {modelDescriptions[settings.model]}
+                        </p>
+                        <button
+                          type="button"
+                          onClick={() => {
+                            void queryEnvironment();
+                          }}
+                          disabled={queryingEnvironment}
+                          className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline"
+                        >
+                          {queryingEnvironment ? "Checking environment..." : "Check installed models and GPU"}
+                        </button>
+                        {environment && (
+                          <div className="rounded-xl border border-[#00E5FF]/20 px-4 py-3 text-xs text-ink-muted">
+                            <p>
+                              Runs on <code>{environment.device}</code>
+                              {environment.cudaDevice ? ` (${environment.cudaDevice})` : ""} · torch{" "}
+                              {environment.torchVersion ?? "unknown"}
+                              {environment.cudaVersion ? ` · CUDA ${environment.cudaVersion}` : ""}
+                            </p>
+                            {environment.models.map((model) => (
+                              <p key={model.id} className="mt-1">
+                                {model.id}: {model.cached ? "downloaded" : "not downloaded yet"}
+                              </p>
+                            ))}
+                          </div>
+                        )}
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">Compare Models</label>
+                        <div className="flex gap-2">
+                          <input
+                            className="scribble-input h-11 flex-1 rounded-xl px-4 text-sm"
+                            value={benchmarkPath}
+                            onChange={(event) => setBenchmarkPath(event.target.value)}
+                            placeholder="Path to a short recording of your voice"
+                          />
+                          <button
+                            type="button"
+                            onClick={() => {
+                              void benchmarkModels();
+                            }}
+                            disabled={benchmarking || !benchmarkPath.trim() || status.phase !== "idle"}
+                            className="rounded-xl border border-[#00E5FF]/20 px-4 text-sm text-ink-muted hover:border-[#00E5FF]/40 disabled:opacity-50"
+                          >
+                            {benchmarking ? "Running..." : "Run"}
+                          </button>
+                        </div>
+                        {benchmarks && (
+                          <div className="grid gap-2 sm:grid-cols-2">
+                            {benchmarks.map((benchmark) => (
+                              <div
+                                key={benchmark.model}
+                                className="rounded-xl border border-[#00E5FF]/20 px-4 py-3 text-xs text-ink-muted"
+                              >
+                                <p className="font-medium">{modelLabels[benchmark.model]}</p>
+                                <p className="mt-1 text-ink-faint">
+                                  {(benchmark.elapsedMs / 1000).toFixed(1)} s total
+                                  {benchmark.inferenceMs !== null
+                                    ? ` · ${(benchmark.inferenceMs / 1000).toFixed(1)} s inference`
+                                    : ""}
+                                </p>
+                                <p className={`mt-2 ${benchmark.error ? "text-rose-400" : ""}`}>
+                                  {benchmark.error ?? benchmark.transcript}
+                                </p>
+                              </div>
+                            ))}
+                          </div>
+                        )}
+                        <p className="text-xs text-ink-faint">
+                          Transcribes the file with both models on this machine. A model that is not downloaded yet is fetched first, which counts towards its time.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">Compute Device</label>
+                        <select
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.computeDevice}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              computeDevice: event.target.value as ComputeDevice,
+                            }))
+                          }
+                        >
+                          <option value="auto">Automatic</option>
+                          <option value="cpu">CPU</option>
+                          <option value="cuda">NVIDIA GPU (CUDA)</option>
+                          <option value="mps">Apple GPU (MPS)</option>
+                        </select>
+                        <p className="text-xs text-ink-faint">
+                          Force CPU to keep the GPU free, or force GPU when automatic picks CPU.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Decoding (advanced)
+                        </label>
+                        <div className="grid grid-cols-3 gap-2">
+                          <input
+                            type="number"
+                            min={16}
+                            max={4096}
+                            step={16}
+                            placeholder="Max tokens"
+                            aria-label="Max new tokens"
+                            className="scribble-input h-10 rounded-xl px-3 text-sm"
+                            value={settings.maxNewTokens ?? ""}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                maxNewTokens:
+                                  event.target.value === "" ? null : Number(event.target.value),
+                              }))
+                            }
+                          />
+                          <input
+                            type="number"
+                            min={1}
+                            max={10}
+                            step={1}
+                            placeholder="Beam size"
+                            aria-label="Beam size"
+                            className="scribble-input h-10 rounded-xl px-3 text-sm"
+                            value={settings.beamSize ?? ""}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                beamSize: event.target.value === "" ? null : Number(event.target.value),
+                              }))
+                            }
+                          />
+                          <input
+                            type="number"
+                            min={0}
+                            max={2}
+                            step={0.1}
+                            placeholder="Temperature"
+                            aria-label="Temperature"
+                            className="scribble-input h-10 rounded-xl px-3 text-sm"
+                            value={settings.temperature ?? ""}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                temperature:
+                                  event.target.value === "" ? null : Number(event.target.value),
+                              }))
+                            }
+                          />
+                        </div>
+                        <p className="text-xs text-ink-faint">
+                          Leave blank to use the model defaults. Wider beams can help in noisy rooms at the cost of speed.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.removeFillers}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                removeFillers: event.target.checked,
+                              }))
+                            }
+                          />
+                          Remove filler words from transcripts
+                        </label>
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.fillerWords.join(", ")}
+                          disabled={!settings.removeFillers}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              fillerWords: event.target.value.split(",").map((word) => word.trim()),
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Comma-separated. Only whole words match, so "um" never touches "umbrella".
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.applyVoiceCommands}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                applyVoiceCommands: event.target.checked,
+                              }))
+                            }
+                          />
+                          Turn spoken commands into punctuation
+                        </label>
+                        {settings.voiceCommands.map((command, index) => (
+                          <div key={index} className="grid grid-cols-[1fr_6rem_auto] gap-2">
+                            <input
+                              className="scribble-input h-10 rounded-xl px-3 text-sm"
+                              value={command.phrase}
+                              placeholder="new line"
+                              disabled={!settings.applyVoiceCommands}
+                              onChange={(event) => updateVoiceCommand(index, { phrase: event.target.value })}
+                            />
+                            <input
+                              className="scribble-input h-10 rounded-xl px-3 font-mono text-sm"
+                              value={command.output}
+                              placeholder="\n"
+                              disabled={!settings.applyVoiceCommands}
+                              onChange={(event) => updateVoiceCommand(index, { output: event.target.value })}
+                            />
+                            <button
+                              type="button"
+                              disabled={!settings.applyVoiceCommands}
+                              onClick={() =>
+                                setSettings((previous) => ({
+                                  ...previous,
+                                  voiceCommands: previous.voiceCommands.filter((_, position) => position !== index),
+                                }))
+                              }
+                              className="rounded-xl border border-[#00E5FF]/20 px-3 text-xs text-ink-subtle hover:border-[#00E5FF]/40 disabled:opacity-50"
+                            >
+                              Remove
+                            </button>
+                          </div>
+                        ))}
+                        <button
+                          type="button"
+                          disabled={!settings.applyVoiceCommands}
+                          onClick={() =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              voiceCommands: [...previous.voiceCommands, { phrase: "", output: "" }],
+                            }))
+                          }
+                          className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline disabled:opacity-50"
+                        >
+                          Add voice command
+                        </button>
+                        <p className="text-xs text-ink-faint">
+                          Say the phrase to insert the text on the right; use \n for a line break. "Period" and similar only apply at the end of a sentence, so "the trial period ended" stays as spoken.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Post-Process Command
+                        </label>
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 font-mono text-sm"
+                          value={settings.postProcessCommand ?? ""}
+                          placeholder="e.g. python ~/cleanup.py"
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              postProcessCommand: event.target.value || null,
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Runs in your shell with the transcript on stdin; its output is inserted instead. If it fails or takes over 15 seconds, the original transcript is used.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Append After Each Dictation
+                        </label>
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 font-mono text-sm"
+                          value={settings.appendSuffix}
+                          placeholder="e.g. \n"
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              appendSuffix: event.target.value,
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Added to the end of every inserted transcript. Use \n for a new line or \t for a tab.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Paste Template
+                        </label>
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 font-mono text-sm"
+                          value={settings.pasteTemplate ?? ""}
+                          placeholder="e.g. - {text}"
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              pasteTemplate: event.target.value || null,
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Wraps each dictation before it is inserted; {"{text}"} marks where the transcript goes.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Long Recording Chunks (seconds)
+                        </label>
+                        <div className="grid grid-cols-2 gap-3">
+                          <input
+                            type="number"
+                            min={0}
+                            max={600}
+                            className="scribble-input h-11 rounded-xl px-4 text-sm"
+                            value={settings.chunkLengthSecs}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                chunkLengthSecs: Math.max(0, Number(event.target.value) || 0),
+                              }))
+                            }
+                          />
+                          <input
+                            type="number"
+                            min={0}
+                            className="scribble-input h-11 rounded-xl px-4 text-sm"
+                            value={settings.chunkOverlapSecs}
+                            disabled={settings.chunkLengthSecs === 0}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                chunkOverlapSecs: Math.max(0, Number(event.target.value) || 0),
+                              }))
+                            }
+                          />
+                        </div>
+                        <p className="text-xs text-ink-faint">
+                          Window length and overlap. Longer recordings are transcribed piece by piece and joined; set the window to 0 to always send the whole recording.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Language (searchable)
+                        </label>
+                        <div className="relative">
+                          <input
+                            className="scribble-input h-11 w-full rounded-xl px-4 text-sm"
+                            value={languageQuery}
+                            onFocus={() => setLanguageMenuOpen(true)}
+                            onChange={(event) => {
+                              setLanguageQuery(event.target.value);
+                              setLanguageMenuOpen(true);
+                            }}
+                            onBlur={() => {
+                              window.setTimeout(() => {
+                                const resolved = resolveLanguageInput(languageQuery);
+                                if (resolved) {
+                                  chooseLanguage(resolved.code);
+                                } else {
+                                  setLanguageQuery(formatLanguageLabel(settings.language));
+                                }
+                                setLanguageMenuOpen(false);
+                              }, 120);
+                            }}
+                            onKeyDown={(event) => {
+                              if (event.key === "Enter") {
+                                event.preventDefault();
+                                const resolved = resolveLanguageInput(languageQuery);
+                                if (resolved) {
+                                  chooseLanguage(resolved.code);
+                                } else if (filteredLanguages[0]) {
+                                  chooseLanguage(filteredLanguages[0].code);
+                                }
+                              }
+
+                              if (event.key === "Escape") {
+                                setLanguageQuery(formatLanguageLabel(settings.language));
+                                setLanguageMenuOpen(false);
+                              }
+                            }}
+                            placeholder="Search language name or code"
+                          />
+
+                          {languageMenuOpen && (
+                            <div className="scribble-border absolute z-20 mt-1 max-h-56 w-full overflow-y-auto rounded-xl border bg-popover p-1">
+                              {filteredLanguages.slice(0, 20).map((language) => (
+                                <button
+                                  key={language.code}
                                   type="button"
                                   onMouseDown={(event) => {
                                     event.preventDefault();
@@ -691,54 +2442,696 @@ function SettingsPage() {
                                   }}
                                   className={`block w-full rounded-lg px-3 py-2 text-left text-sm transition ${
                                     settings.language === language.code
-                                      ? "bg-[#00E5FF]/20 text-[#00E5FF]"
-                                      : "text-slate-300 hover:bg-[#00E5FF]/10"
+                                      ? "bg-[#00E5FF]/20 text-ink-brand"
+                                      : "text-ink-muted hover:bg-[#00E5FF]/10"
                                   }`}
                                 >
                                   {language.label} ({language.code})
                                 </button>
                               ))}
                               {filteredLanguages.length === 0 && (
-                                <p className="px-3 py-2 text-xs text-slate-500">
+                                <p className="px-3 py-2 text-xs text-ink-faint">
                                   No language matches your search.
                                 </p>
                               )}
                             </div>
                           )}
                         </div>
-                        <p className="text-xs text-slate-500">
-                          Selected language code: <code>{settings.language}</code>
-                        </p>
-                      </div>
+                        <p className="text-xs text-ink-faint">
+                          Selected language code: <code>{settings.language}</code>
+                        </p>
+                        {detectedLanguage && (
+                          <p className="text-xs text-ink-subtle">
+                            Detected: {formatLanguageLabel(detectedLanguage)}
+                          </p>
+                        )}
+                        {languageSuggestion && (
+                          <div className="rounded-xl border border-[#00E5FF]/20 px-4 py-3 text-xs text-ink-muted">
+                            {languageSuggestion.pinned ? (
+                              <p>
+                                Pinned {formatLanguageLabel(languageSuggestion.language)} after{" "}
+                                {languageSuggestion.detections} matching detections.
+                              </p>
+                            ) : (
+                              <p>
+                                Your last {languageSuggestion.detections} dictations were in{" "}
+                                {formatLanguageLabel(languageSuggestion.language)}. Pin it to skip detection?
+                              </p>
+                            )}
+                            <div className="mt-2 flex gap-3">
+                              {!languageSuggestion.pinned && (
+                                <button
+                                  type="button"
+                                  onClick={() => {
+                                    void pinSuggestedLanguage(languageSuggestion.language);
+                                  }}
+                                  className="text-ink-accent underline-offset-2 hover:underline"
+                                >
+                                  Pin language
+                                </button>
+                              )}
+                              <button
+                                type="button"
+                                onClick={() => setLanguageSuggestion(null)}
+                                className="text-ink-subtle underline-offset-2 hover:underline"
+                              >
+                                Dismiss
+                              </button>
+                            </div>
+                          </div>
+                        )}
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Suggest Pinning After
+                        </label>
+                        <input
+                          type="number"
+                          min={0}
+                          max={50}
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.languagePinAfter}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              languagePinAfter: Math.max(0, Math.round(Number(event.target.value) || 0)),
+                            }))
+                          }
+                        />
+                        <label className="flex items-center gap-3 text-sm text-ink-muted">
+                          <input
+                            type="checkbox"
+                            checked={settings.autoPinLanguage}
+                            disabled={settings.languagePinAfter === 0}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                autoPinLanguage: event.target.checked,
+                              }))
+                            }
+                          />
+                          Pin the language automatically instead of asking
+                        </label>
+                        <p className="text-xs text-ink-faint">
+                          With automatic detection, counts dictations in a row detected as the same language. 0 turns suggestions off.
+                        </p>
+                      </div>
+                    </div>
+                  </div>
+                )}
+
+                {activeTab === "audio" && (
+                  <div className="scribble-border scribble-glow rounded-2xl p-6">
+                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-ink-brand"><IconAudio className="h-5 w-5" />Audio</h2>
+                    <div className="grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Capture Source
+                      </label>
+                      <select
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.captureSource}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            captureSource: event.target.value as CaptureSource,
+                          }))
+                        }
+                      >
+                        <option value="microphone">Microphone</option>
+                        <option value="systemLoopback">System audio (captioning)</option>
+                      </select>
+                      <p className="text-xs text-ink-faint">
+                        System audio transcribes what your computer is playing. Needs Windows or a PulseAudio/PipeWire monitor source on Linux.
+                      </p>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Retries When the Device Is Busy
+                      </label>
+                      <input
+                        type="number"
+                        min={0}
+                        max={5}
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.recorderRetries}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            recorderRetries: Math.min(5, Math.max(0, Number(event.target.value) || 0)),
+                          }))
+                        }
+                      />
+                      <p className="text-xs text-ink-faint">
+                        Tries opening the microphone again when another app briefly holds it, e.g. a browser or a call app.
+                      </p>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Capture Sample Rate
+                      </label>
+                      <select
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.captureSampleRate ?? ""}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            captureSampleRate: event.target.value ? Number(event.target.value) : null,
+                          }))
+                        }
+                      >
+                        <option value="">Device default</option>
+                        <option value="16000">16 kHz</option>
+                        <option value="22050">22.05 kHz</option>
+                        <option value="44100">44.1 kHz</option>
+                        <option value="48000">48 kHz</option>
+                      </select>
+                      <p className="text-xs text-ink-faint">
+                        16 kHz matches the speech model and skips resampling. Falls back to the device default if unsupported.
+                      </p>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Microphone Input
+                      </label>
+                      <select
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.inputDevice}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            inputDevice: event.target.value,
+                          }))
+                        }
+                      >
+                        {(inputDevices.includes(settings.inputDevice)
+                          ? inputDevices
+                          : [...inputDevices, settings.inputDevice]
+                        ).map((deviceName) => (
+                          <option key={deviceName} value={deviceName}>
+                            {deviceName === "default" ? "System Default" : deviceName}
+                          </option>
+                        ))}
+                      </select>
+                      <p className="text-xs text-ink-faint">
+                        Pick which microphone is used when recording starts.
+                      </p>
+                      {inputDeviceMissing && (
+                        <p className="text-xs text-amber-400">
+                          The saved microphone is not connected. Recording uses a fallback device or the system default until it is back.
+                        </p>
+                      )}
+                      {(() => {
+                        const info = deviceDetails.find(
+                          (device) => device.name === settings.inputDevice,
+                        );
+                        if (!info || info.sampleRate === null) {
+                          return null;
+                        }
+
+                        const lowRate = info.sampleRate < 16000;
+                        const manyChannels = (info.channels ?? 1) > 2;
+                        return (
+                          <p
+                            className={`text-xs ${
+                              lowRate || manyChannels ? "text-amber-400" : "text-ink-faint"
+                            }`}
+                          >
+                            {info.sampleRate / 1000} kHz · {info.channels} channel(s) · {info.sampleFormat}
+                            {lowRate ? " — below 16 kHz, transcription accuracy will suffer." : ""}
+                            {!lowRate && manyChannels ? " — multichannel device, check you picked the right input." : ""}
+                          </p>
+                        );
+                      })()}
+                      {selectedDevice && (
+                        <p className="text-xs text-ink-subtle">
+                          Last recording used {selectedDevice.name}
+                          {selectedDevice.fellBack ? " (fallback)" : ""}.
+                        </p>
+                      )}
+                      <button
+                        type="button"
+                        disabled={testingMic}
+                        onClick={() => {
+                          void testMicrophone();
+                        }}
+                        className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline disabled:opacity-50"
+                      >
+                        {testingMic ? "Recording 3 seconds, say something..." : "Test microphone"}
+                      </button>
+                      {micTest && (
+                        <div className="grid gap-1">
+                          <div className="h-2 overflow-hidden rounded-full bg-track">
+                            <div
+                              className={`h-full ${micTest.silent ? "bg-rose-400" : "bg-emerald-400"}`}
+                              style={{ width: `${Math.min(100, Math.round(micTest.peak * 100))}%` }}
+                            />
+                          </div>
+                          <p className={`text-xs ${micTest.silent ? "text-rose-400" : "text-emerald-400"}`}>
+                            {micTest.silent
+                              ? "No speech detected. Check the microphone or lower the silence threshold."
+                              : `Mic is working. Peak ${Math.round(micTest.peak * 100)}%, average level ${(micTest.rms * 100).toFixed(1)}%.`}
+                          </p>
+                        </div>
+                      )}
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Fallback Microphones
+                      </label>
+                      {settings.fallbackDevices.map((deviceName, index) => (
+                        <div key={index} className="grid grid-cols-[1fr_auto] gap-2">
+                          <select
+                            className="scribble-input h-10 rounded-xl px-3 text-sm"
+                            value={deviceName}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                fallbackDevices: previous.fallbackDevices.map((name, position) =>
+                                  position === index ? event.target.value : name,
+                                ),
+                              }))
+                            }
+                          >
+                            {(inputDevices.includes(deviceName)
+                              ? inputDevices
+                              : [...inputDevices, deviceName]
+                            ).map((name) => (
+                              <option key={name} value={name}>
+                                {name === "default" ? "System Default" : name}
+                              </option>
+                            ))}
+                          </select>
+                          <button
+                            type="button"
+                            onClick={() =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                fallbackDevices: previous.fallbackDevices.filter(
+                                  (_, position) => position !== index,
+                                ),
+                              }))
+                            }
+                            className="rounded-xl border border-[#00E5FF]/20 px-3 text-xs text-ink-subtle hover:border-[#00E5FF]/40"
+                          >
+                            Remove
+                          </button>
+                        </div>
+                      ))}
+                      <button
+                        type="button"
+                        onClick={() =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            fallbackDevices: [...previous.fallbackDevices, "default"],
+                          }))
+                        }
+                        className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline"
+                      >
+                        Add fallback microphone
+                      </button>
+                      <p className="text-xs text-ink-faint">
+                        Tried in order when the microphone above is not connected, before the system default.
+                      </p>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Recording Format
+                      </label>
+                      <select
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.recordingFormat}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            recordingFormat: event.target.value as RecordingFormat,
+                          }))
+                        }
+                      >
+                        <option value="pcm16">16-bit PCM</option>
+                        <option value="pcm24">24-bit PCM</option>
+                        <option value="float32">32-bit Float</option>
+                      </select>
+                      <p className="text-xs text-ink-faint">
+                        Higher bit depths preserve more detail for archiving at the cost of larger files.
+                      </p>
+                      <label className="flex items-center gap-3 text-sm text-ink-muted">
+                        <input
+                          type="checkbox"
+                          checked={settings.keepRecordings}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              keepRecordings: event.target.checked,
+                            }))
+                          }
+                        />
+                        Keep recordings with a JSON file of model, language, and transcript
+                      </label>
+                      <label className="flex items-center gap-3 text-sm text-ink-muted">
+                        <input
+                          type="checkbox"
+                          checked={settings.saveHistory}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              saveHistory: event.target.checked,
+                            }))
+                          }
+                        />
+                        Save transcripts to the history window
+                      </label>
+                      {settings.keepRecordings && recordingsUsage && (
+                        <div className="flex flex-wrap items-center gap-3 text-xs text-ink-subtle">
+                          <span>
+                            {recordingsUsage.recordingCount} kept, {(recordingsUsage.totalBytes / (1024 * 1024)).toFixed(1)} MB
+                          </span>
+                          <button
+                            type="button"
+                            onClick={() => {
+                              void pruneRecordings({ keepLast: 50 });
+                            }}
+                            className="text-ink-accent underline-offset-2 hover:underline"
+                          >
+                            Keep newest 50
+                          </button>
+                          <button
+                            type="button"
+                            onClick={() => {
+                              void pruneRecordings({ olderThanDays: 30 });
+                            }}
+                            className="text-ink-accent underline-offset-2 hover:underline"
+                          >
+                            Delete older than 30 days
+                          </button>
+                        </div>
+                      )}
+                      <button
+                        type="button"
+                        onClick={() => {
+                          void openRecordingsFolder();
+                        }}
+                        className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline"
+                      >
+                        Open recordings folder
+                      </button>
+                      <button
+                        type="button"
+                        onClick={() => {
+                          void openHistory();
+                        }}
+                        className="justify-self-start text-xs text-ink-accent underline-offset-2 hover:underline"
+                      >
+                        Open transcript history
+                      </button>
                     </div>
-                  </div>
-                )}
 
-                {activeTab === "audio" && (
-                  <div className="scribble-border scribble-glow rounded-2xl p-6">
-                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-[#00E5FF]"><IconAudio className="h-5 w-5" />Audio</h2>
-                    <div className="grid gap-2">
-                      <label className="text-sm font-medium text-slate-300">
-                        Microphone Input
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Overlay Linger (ms)
+                      </label>
+                      <input
+                        type="number"
+                        min={0}
+                        max={10000}
+                        step={100}
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.overlayHideDelayMs}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            overlayHideDelayMs: Math.max(0, Number(event.target.value) || 0),
+                          }))
+                        }
+                      />
+                      <p className="text-xs text-ink-faint">
+                        Keep the overlay showing "Done" this long after a dictation. Set to 0 to hide it immediately.
+                      </p>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Progress Update Interval (ms)
+                      </label>
+                      <input
+                        type="number"
+                        min={0}
+                        max={10000}
+                        step={100}
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.progressIntervalMs}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            progressIntervalMs: Math.max(0, Number(event.target.value) || 0),
+                          }))
+                        }
+                      />
+                      <p className="text-xs text-ink-faint">
+                        How often the overlay's elapsed-time counter updates while transcribing. Set to 0 to turn it off.
+                      </p>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Theme
                       </label>
                       <select
                         className="scribble-input h-11 rounded-xl px-4 text-sm"
-                        value={settings.inputDevice}
+                        value={settings.theme}
                         onChange={(event) =>
                           setSettings((previous) => ({
                             ...previous,
-                            inputDevice: event.target.value,
+                            theme: event.target.value as ThemePreference,
+                          }))
+                        }
+                      >
+                        <option value="system">Follow system</option>
+                        <option value="light">Light</option>
+                        <option value="dark">Dark</option>
+                      </select>
+                      <p className="text-xs text-ink-faint">
+                        Applies to the settings window and the overlay.
+                      </p>
+                    </div>
+
+                    <label className="mt-5 flex items-center gap-3 text-sm text-ink-muted">
+                      <input
+                        type="checkbox"
+                        checked={settings.showOverlay}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            showOverlay: event.target.checked,
+                          }))
+                        }
+                      />
+                      Show the floating overlay (status stays in the tray icon when off)
+                    </label>
+
+                    <label className="mt-5 flex items-center gap-3 text-sm text-ink-muted">
+                      <input
+                        type="checkbox"
+                        checked={settings.startMinimized}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            startMinimized: event.target.checked,
+                          }))
+                        }
+                      />
+                      Start in the tray without opening this window
+                    </label>
+
+                    <label className="mt-5 flex items-center gap-3 text-sm text-ink-muted">
+                      <input
+                        type="checkbox"
+                        disabled={!settings.showOverlay}
+                        checked={settings.overlayClickable}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            overlayClickable: event.target.checked,
+                          }))
+                        }
+                      />
+                      Click the overlay to stop recording or cancel transcription
+                    </label>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Overlay Monitor
+                      </label>
+                      <select
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        disabled={!settings.showOverlay}
+                        value={settings.overlayMonitor}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            overlayMonitor: event.target.value as OverlayMonitor,
+                          }))
+                        }
+                      >
+                        <option value="focusedWindow">Monitor with the focused window</option>
+                        <option value="primary">Primary monitor</option>
+                      </select>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Overlay Text
+                      </label>
+                      {overlayMessageFields.map((field) => (
+                        <div key={field.phase} className="grid grid-cols-[7rem_1fr] items-center gap-2">
+                          <span className="text-xs text-ink-subtle">{field.label}</span>
+                          <input
+                            className="scribble-input h-10 rounded-xl px-3 text-sm"
+                            disabled={!settings.showOverlay}
+                            value={settings.overlayMessages[field.phase] ?? ""}
+                            placeholder={field.placeholder}
+                            onChange={(event) =>
+                              setSettings((previous) => ({
+                                ...previous,
+                                overlayMessages: {
+                                  ...previous.overlayMessages,
+                                  [field.phase]: event.target.value,
+                                },
+                              }))
+                            }
+                          />
+                        </div>
+                      ))}
+                      <p className="text-xs text-ink-faint">
+                        Leave a field empty for the built-in text. {"{message}"} inserts the app's own message, e.g. the reason for an error.
+                      </p>
+                    </div>
+
+                    <label className="mt-5 flex items-center gap-3 text-sm text-ink-muted">
+                      <input
+                        type="checkbox"
+                        checked={settings.audioCues}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            audioCues: event.target.checked,
+                          }))
+                        }
+                      />
+                      Play a short tone when dictation starts and stops
+                    </label>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Cue Output Device
+                      </label>
+                      <select
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.outputDevice}
+                        disabled={!settings.audioCues}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            outputDevice: event.target.value,
                           }))
                         }
                       >
-                        {inputDevices.map((deviceName) => (
+                        {(outputDevices.includes(settings.outputDevice)
+                          ? outputDevices
+                          : [...outputDevices, settings.outputDevice]
+                        ).map((deviceName) => (
                           <option key={deviceName} value={deviceName}>
                             {deviceName === "default" ? "System Default" : deviceName}
                           </option>
                         ))}
                       </select>
-                      <p className="text-xs text-slate-500">
-                        Pick which microphone is used when recording starts.
+                      <p className="text-xs text-ink-faint">
+                        Cues fall back to the system default when this device is disconnected.
+                      </p>
+                    </div>
+
+                    <div className="mt-5 grid gap-2">
+                      <label className="text-sm font-medium text-ink-muted">
+                        Silence Threshold
+                      </label>
+                      <input
+                        type="number"
+                        min={0}
+                        max={1}
+                        step={0.001}
+                        className="scribble-input h-11 rounded-xl px-4 text-sm"
+                        value={settings.silenceThreshold}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            silenceThreshold: Math.max(0, Number(event.target.value) || 0),
+                          }))
+                        }
+                      />
+                      <p className="text-xs text-ink-faint">
+                        Recordings quieter than this level are dropped without transcribing. Set to 0 to disable.
+                      </p>
+                    </div>
+
+                    <label className="mt-5 flex items-center gap-3 text-sm text-ink-muted">
+                      <input
+                        type="checkbox"
+                        checked={settings.quietEmptyTranscripts}
+                        onChange={(event) =>
+                          setSettings((previous) => ({
+                            ...previous,
+                            quietEmptyTranscripts: event.target.checked,
+                          }))
+                        }
+                      />
+                      Show "Nothing heard" instead of an error when no words were recognized
+                    </label>
+
+                    <div className="mt-5 grid gap-2 sm:grid-cols-2">
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Trim Threshold
+                        </label>
+                        <input
+                          type="number"
+                          min={0}
+                          max={1}
+                          step={0.005}
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.trimThreshold}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              trimThreshold: Math.max(0, Number(event.target.value) || 0),
+                            }))
+                          }
+                        />
+                      </div>
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Trim Margin (ms)
+                        </label>
+                        <input
+                          type="number"
+                          min={0}
+                          step={50}
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.trimMarginMs}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              trimMarginMs: Math.max(0, Number(event.target.value) || 0),
+                            }))
+                          }
+                        />
+                      </div>
+                      <p className="text-xs text-ink-faint sm:col-span-2">
+                        Quiet audio before and after speech is cut before transcribing. Set the threshold to 0 to disable.
                       </p>
                     </div>
                   </div>
@@ -746,14 +3139,15 @@ function SettingsPage() {
 
                 {activeTab === "runtime" && (
                   <div className="scribble-border scribble-glow rounded-2xl p-6">
-                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-[#00E5FF]"><IconRuntime className="h-5 w-5" />Runtime</h2>
+                    <h2 className="mb-5 flex items-center gap-2 text-lg font-semibold text-ink-brand"><IconRuntime className="h-5 w-5" />Runtime</h2>
                     <div className="space-y-5">
                       <div className="grid gap-2">
-                        <label className="text-sm font-medium text-slate-300">
+                        <label className="text-sm font-medium text-ink-muted">
                           Python Command
                         </label>
                         <input
                           className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          disabled={settings.useBundledPython}
                           value={settings.pythonCommand}
                           onChange={(event) =>
                             setSettings((previous) => ({
@@ -763,14 +3157,205 @@ function SettingsPage() {
                           }
                           placeholder="python"
                         />
-                        <p className="text-xs text-slate-500">
+                        <p className="text-xs text-ink-faint">
                           Use <code>python</code>, <code>py</code>, or full path to interpreter.
                         </p>
                       </div>
 
+                      <label className="flex items-center gap-3 text-sm text-ink-muted">
+                        <input
+                          type="checkbox"
+                          checked={settings.useBundledPython}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              useBundledPython: event.target.checked,
+                            }))
+                          }
+                        />
+                        Use the Python runtime bundled with the app
+                      </label>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Virtualenv Path
+                        </label>
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.venvPath}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              venvPath: event.target.value,
+                            }))
+                          }
+                          placeholder="Leave empty to use the Python command directly"
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Created with the Python command if missing. ASR dependencies are installed here instead of your global environment.
+                        </p>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Model Download Folder
+                        </label>
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 text-sm"
+                          value={settings.modelCacheDir ?? ""}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              modelCacheDir: event.target.value || null,
+                            }))
+                          }
+                          placeholder="Leave empty to use the default Hugging Face cache"
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Models are several GB each. Point this at a larger drive; models already downloaded elsewhere are not moved.
+                        </p>
+                      </div>
+
+                      <label className="flex items-center gap-3 text-sm text-ink-muted">
+                        <input
+                          type="checkbox"
+                          checked={settings.autoInstallDeps}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              autoInstallDeps: event.target.checked,
+                            }))
+                          }
+                        />
+                        Install missing ASR dependencies automatically with pip
+                      </label>
+
+                      <div className="grid gap-2">
+                        <label className="text-sm font-medium text-ink-muted">
+                          Pinned Versions
+                        </label>
+                        <input
+                          className="scribble-input h-11 rounded-xl px-4 font-mono text-sm"
+                          value={settings.dependencyPins.join(", ")}
+                          placeholder="e.g. torch==2.5.1, torchvision==0.20.1"
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              dependencyPins: event.target.value.split(",").map((pin) => pin.trim()),
+                            }))
+                          }
+                        />
+                        <p className="text-xs text-ink-faint">
+                          Used when a missing package is installed. Packages that are already installed are never upgraded.
+                        </p>
+                      </div>
+
+                      <label className="flex items-center gap-3 text-sm text-ink-muted">
+                        <input
+                          type="checkbox"
+                          checked={settings.queueWhileBootstrapping}
+                          onChange={(event) =>
+                            setSettings((previous) => ({
+                              ...previous,
+                              queueWhileBootstrapping: event.target.checked,
+                            }))
+                          }
+                        />
+                        Record during ASR setup and transcribe once it is ready
+                      </label>
+
                       <div className={`rounded-xl border px-4 py-3 text-sm ${statusColor}`}>
                         <p className="font-medium">ASR bootstrap state</p>
-                        <p className="mt-1 text-slate-300">{status.message ?? "Ready"}</p>
+                        {bootstrapProgress && (
+                          <ol className="mt-2 space-y-1 text-xs">
+                            {bootstrapSteps.map((step, index) => {
+                              const reachedIndex = bootstrapSteps.findIndex(
+                                (candidate) => candidate.stage === bootstrapProgress.reached,
+                              );
+                              const failed =
+                                bootstrapProgress.stage === "failed" && index === reachedIndex;
+                              const done =
+                                index < reachedIndex || bootstrapProgress.reached === "ready";
+                              const active = !failed && !done && index === reachedIndex;
+                              return (
+                                <li key={step.stage} className="flex items-center gap-2">
+                                  <span
+                                    className={`inline-block h-2 w-2 rounded-full ${
+                                      failed
+                                        ? "bg-rose-400"
+                                        : done
+                                          ? "bg-emerald-400"
+                                          : active
+                                            ? "animate-pulse bg-cyan-400"
+                                            : "bg-ink-faint"
+                                    }`}
+                                  />
+                                  <span
+                                    className={
+                                      done || active || failed ? "text-ink-soft" : "text-ink-faint"
+                                    }
+                                  >
+                                    {step.label}
+                                  </span>
+                                  {failed && (
+                                    <button
+                                      type="button"
+                                      onClick={() => {
+                                        void retryBootstrap();
+                                      }}
+                                      className="text-ink-accent underline-offset-2 hover:underline"
+                                    >
+                                      Retry
+                                    </button>
+                                  )}
+                                </li>
+                              );
+                            })}
+                          </ol>
+                        )}
+                        <p className="mt-1 text-ink-muted">{status.message ?? "Ready"}</p>
+                        <button
+                          type="button"
+                          onClick={() => {
+                            void warmupModel();
+                          }}
+                          disabled={status.phase !== "idle"}
+                          className="mt-2 text-xs text-ink-accent underline-offset-2 hover:underline disabled:opacity-50"
+                        >
+                          Warm up model
+                        </button>
+                      </div>
+
+                      <div className="grid gap-2">
+                        <button
+                          type="button"
+                          onClick={() => {
+                            void runDiagnostics();
+                          }}
+                          disabled={diagnosing}
+                          className="scribble-button scribble-button-secondary inline-flex h-10 items-center justify-self-start rounded-xl px-4 text-sm"
+                        >
+                          {diagnosing ? "Running diagnostics..." : "Run Diagnostics"}
+                        </button>
+                        {diagnostics.length > 0 && (
+                          <ul className="space-y-2">
+                            {diagnostics.map((check) => (
+                              <li
+                                key={check.name}
+                                className={`rounded-xl border px-4 py-2 text-sm ${
+                                  check.passed
+                                    ? "border-emerald-400/30 bg-emerald-400/10 text-emerald-400"
+                                    : "border-rose-400/30 bg-rose-400/10 text-rose-400"
+                                }`}
+                              >
+                                <p className="font-medium">
+                                  {check.passed ? "✓" : "✗"} {check.name}
+                                </p>
+                                <p className="mt-1 text-xs text-ink-muted">{check.detail}</p>
+                              </li>
+                            ))}
+                          </ul>
+                        )}
                       </div>
                     </div>
                   </div>
@@ -778,7 +3363,7 @@ function SettingsPage() {
               </div>
             </div>
 
-            <footer className="flex items-center gap-3 border-t border-[#00E5FF]/20 bg-[#060d18]/75 p-4">
+            <footer className="flex items-center gap-3 border-t border-[#00E5FF]/20 bg-panel/75 p-4">
               <button
                 type="button"
                 onClick={persistSettings}
@@ -802,6 +3387,34 @@ function SettingsPage() {
                 )}
                 {status.phase === "listening" ? "Stop Dictation" : "Start Dictation"}
               </button>
+
+              {status.phase === "transcribing" && (
+                <button
+                  type="button"
+                  onClick={abortTranscription}
+                  className="scribble-button scribble-button-secondary inline-flex h-11 items-center gap-2 rounded-xl px-6 text-sm"
+                >
+                  <IconStop className="h-4 w-4" />
+                  Cancel Transcription
+                </button>
+              )}
+
+              <button
+                type="button"
+                onClick={resetSettings}
+                disabled={saving}
+                className="ml-auto text-xs text-ink-faint underline-offset-2 hover:text-ink-accent hover:underline"
+              >
+                Reset to defaults
+              </button>
+
+              <button
+                type="button"
+                onClick={quitApp}
+                className="text-xs text-ink-faint underline-offset-2 hover:text-ink-accent hover:underline"
+              >
+                Quit
+              </button>
             </footer>
           </section>
         </div>
@@ -811,19 +3424,37 @@ function SettingsPage() {
 }
 
 function App() {
-  const isOverlayWindow = useMemo(() => {
+  const windowKind = useMemo(() => {
     if (typeof window === "undefined") {
-      return false;
+      return "settings";
     }
 
     const params = new URLSearchParams(window.location.search);
-    return params.get("overlay") === "1";
+    if (params.get("overlay") === "1") {
+      return "overlay";
+    }
+
+    if (params.get("history") === "1") {
+      return "history";
+    }
+
+    return params.get("review") === "1" ? "review" : "settings";
   }, []);
 
-  if (isOverlayWindow) {
+  useDocumentTheme();
+
+  if (windowKind === "overlay") {
     return <OverlayPill />;
   }
 
+  if (windowKind === "review") {
+    return <ReviewPanel />;
+  }
+
+  if (windowKind === "history") {
+    return <HistoryPanel />;
+  }
+
   return <SettingsPage />;
 }
 